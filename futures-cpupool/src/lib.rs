@@ -42,14 +42,25 @@ extern crate crossbeam;
 extern crate futures;
 extern crate num_cpus;
 
+use std::any::Any;
+use std::cell::RefCell;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
 use std::panic::{self, AssertUnwindSafe};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::ptr;
+use std::slice;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use crossbeam::sync::MsQueue;
+use crossbeam::sync::chase_lev::{self, Steal, Stealer};
 use futures::{IntoFuture, Future, Poll, Async};
-use futures::future::lazy;
+use futures::future::{lazy, CatchUnwind};
 use futures::sync::oneshot::{channel, Sender, Receiver};
 use futures::executor::{self, Run, Executor};
 
@@ -64,6 +75,23 @@ use futures::executor::{self, Run, Executor};
 /// all work has been drained and all references have gone away the worker
 /// threads will be shut down.
 ///
+/// A pool can also be shut down explicitly with `shutdown`, which waits for
+/// the work already spawned to finish, or `shutdown_now`, which drops work
+/// that hasn't started yet. Either way no more work can be spawned afterwards:
+/// `spawn` panics from then on, so code which may race with a shutdown should
+/// use `try_spawn`, which returns an error instead. The `CpuFuture`s of work
+/// dropped by `shutdown_now` are canceled, which `CpuFuture::catch_cancel` can
+/// detect.
+///
+/// By default a pool runs a fixed number of worker threads, but it can also be
+/// configured through `Builder::min_threads` and `Builder::max_threads` to
+/// start more workers when all of them are busy, and to stop the extra ones
+/// once they've been idle for a while.
+///
+/// Each worker thread keeps its own queue of tasks, and workers which run out
+/// of work steal tasks from the others. A task unparked on a worker thread is
+/// run next on that same thread, once the task it's currently running returns.
+///
 /// Currently `CpuPool` implements `Clone` which just clones a new reference to
 /// the underlying thread pool.
 ///
@@ -80,16 +108,27 @@ pub struct CpuPool {
 /// Builder starts with a number of workers equal to the number
 /// of CPUs on the host. But you can change it until you call `create()`.
 pub struct Builder {
-    pool_size: usize,
+    // Whichever of these isn't set is derived from the number of CPUs and the
+    // other one
+    min_threads: Option<usize>,
+    max_threads: Option<usize>,
+    keep_alive: Duration,
     name_prefix: Option<String>,
     after_start: Option<Arc<Fn() + Send + Sync>>,
     before_stop: Option<Arc<Fn() + Send + Sync>>,
+    after_poll: Option<Arc<Fn(Duration) + Send + Sync>>,
 }
 
 struct MySender<F, T> {
     fut: F,
     tx: Option<Sender<T>>,
     keep_running_flag: Arc<AtomicBool>,
+    guard: TaskGuard,
+}
+
+// Counts a spawned task as active until it has completed or been dropped
+struct TaskGuard {
+    inner: Arc<Inner>,
 }
 
 fn _assert() {
@@ -100,13 +139,135 @@ fn _assert() {
 }
 
 struct Inner {
-    queue: MsQueue<Message>,
+    // Tasks submitted from outside of the pool's worker threads. Workers only
+    // look here once their own deque is empty.
+    //
+    // `MsQueue` leaves a placeholder payload uninitialized, which `Run` on its
+    // own doesn't allow, so tasks are always pushed as `Some`.
+    injector: MsQueue<Option<Run>>,
+
+    // Number of tasks in `injector`
+    injected: AtomicUsize,
+
+    // The state of each worker which the other threads can reach, indexed by
+    // worker. Slots are added as workers are started, so there are only as
+    // many as the most workers that have run at once, and a worker which
+    // stops leaves its slot to the next one started.
+    slots: Slots,
+
+    // Number of worker threads, which is kept between `min_threads` and
+    // `max_threads`
+    num_threads: AtomicUsize,
+    min_threads: usize,
+    max_threads: usize,
+    keep_alive: Duration,
+
+    // Lets submitters, which only have a reference, start new workers
+    this: Mutex<Weak<Inner>>,
+
+    // Idle workers sleep on `sleep_cvar`. `idle` counts them, split into
+    // those which have been picked to wake up and those which haven't, so
+    // that submitters only take the lock when there's somebody to wake.
+    idle: AtomicUsize,
+    sleep_lock: Mutex<()>,
+    sleep_cvar: Condvar,
+
+    // Number of spawned tasks which haven't completed or been dropped yet
+    active: AtomicUsize,
+
+    // Set by `shutdown` and `shutdown_now` to refuse new tasks, and by
+    // `shutdown_now` alone to drop tasks rather than run them
+    shutdown: AtomicBool,
+    abort: AtomicBool,
+
+    // Notified once the pool is shut down and no tasks are active
+    shutdown_waiters: Mutex<Vec<Sender<()>>>,
+
+    // Counters reported by `CpuPool::metrics`
+    spawned: AtomicUsize,
+    completed: AtomicUsize,
+    panicked: AtomicUsize,
+
+    // Set once the workers should exit as soon as they're out of work
+    closed: AtomicBool,
     cnt: AtomicUsize,
-    size: usize,
     after_start: Option<Arc<Fn() + Send + Sync>>,
     before_stop: Option<Arc<Fn() + Send + Sync>>,
+    after_poll: Option<Arc<Fn(Duration) + Send + Sync>>,
+    name_prefix: Option<String>,
 }
 
+// The part of a worker's state which other threads can reach. Each slot is
+// allocated on its own so that workers updating their own counters don't
+// contend with each other.
+struct Slot {
+    stealer: Stealer<Run>,
+
+    // Number of tasks in the worker's deque. This is only touched by other
+    // threads when they steal one of the tasks.
+    queued: AtomicUsize,
+
+    // The owning half of the deque while no worker thread is using it, ready
+    // to be handed to a new worker
+    idle_deque: Mutex<Option<chase_lev::Worker<Run>>>,
+
+    stats: Mutex<WorkerMetrics>,
+}
+
+// The workers' slots, which can be read without taking a lock. Slots are only
+// ever added, into a table which is replaced by one twice the size when it
+// fills up. Replaced tables are kept until the pool is dropped so that readers
+// never see one freed, and together they take up less room than the latest.
+struct Slots {
+    // Number of slots added so far, all of which are in `table`
+    len: AtomicUsize,
+    table: AtomicPtr<AtomicPtr<Slot>>,
+
+    // Owns the slots and tables above, and is locked to add a slot
+    owned: Mutex<OwnedSlots>,
+}
+
+struct OwnedSlots {
+    slots: Vec<Arc<Slot>>,
+    tables: Vec<Vec<AtomicPtr<Slot>>>,
+}
+
+// The slots added by the time `Slots::load` was called
+struct SlotsRef<'a> {
+    table: &'a [AtomicPtr<Slot>],
+}
+
+// State owned by each worker thread, reachable through `CURRENT` so that tasks
+// unparked on a worker can be scheduled locally.
+struct WorkerContext {
+    // Identifies the pool this thread belongs to
+    pool: *const Inner,
+    index: usize,
+    slot: Arc<Slot>,
+    deque: chase_lev::Worker<Run>,
+
+    // The most recently unparked task, which the worker runs next as its data
+    // is likely still in cache. Unlike the deque, other workers can't take it.
+    lifo: Option<Run>,
+    lifo_streak: usize,
+    tick: usize,
+}
+
+thread_local!(static CURRENT: RefCell<Option<WorkerContext>> = RefCell::new(None));
+
+// How many tasks may run in a row out of the LIFO slot before other work gets
+// a turn, so that a pair of tasks unparking each other can't starve the rest
+// of the worker's deque.
+const LIFO_LIMIT: usize = 16;
+
+// How often a worker checks the injector before its own deque, so that tasks
+// submitted from outside the pool aren't starved by local work.
+const INJECTOR_INTERVAL: usize = 61;
+
+// `Inner::idle` counts the sleeping workers which haven't been picked to wake
+// up in its low half, and those which have in its high half.
+const IDLE_NOTIFIED: usize = 1 << (mem::size_of::<usize>() * 4);
+
 /// The type of future returned from the `CpuPool::spawn` function, which
 /// proxies the futures running on the thread pool.
 ///
@@ -118,11 +279,96 @@ pub struct CpuFuture<T, E> {
     keep_running_flag: Arc<AtomicBool>,
 }
 
-enum Message {
-    Run(Run),
-    Close,
+/// A future which resolves like a `CpuFuture`, except that it fails with
+/// `CancelError::Canceled` if the spawned future is dropped before completing.
+///
+/// This is created by the `CpuFuture::catch_cancel` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct CatchCancel<T, E> {
+    inner: CpuFuture<T, E>,
+}
+
+/// Error returned by `CatchCancel` futures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CancelError<E> {
+    /// The spawned future was dropped by `CpuPool::shutdown_now` before it
+    /// completed.
+    Canceled,
+
+    /// The spawned future failed with this error.
+    Failed(E),
+}
+
+/// A snapshot of a `CpuPool`'s runtime statistics.
+///
+/// This is returned by `CpuPool::metrics`. The counters are read one at a time
+/// while the pool keeps running, so they may not be exactly consistent with
+/// each other.
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    queue_depth: usize,
+    tasks_spawned: usize,
+    tasks_completed: usize,
+    tasks_panicked: usize,
+    threads: usize,
+    workers: Vec<WorkerMetrics>,
+}
+
+/// Statistics for a single worker thread of a `CpuPool`, as part of `Metrics`.
+#[derive(Clone, Debug, Default)]
+pub struct WorkerMetrics {
+    busy_time: Duration,
+    polls: usize,
+}
+
+/// A future which resolves once a `CpuPool` has been shut down and all of its
+/// tasks have finished.
+///
+/// This is returned by `CpuPool::shutdown` and `CpuPool::shutdown_now`. It
+/// keeps the worker threads alive until the remaining tasks are done.
+#[must_use]
+pub struct Shutdown {
+    inner: Receiver<()>,
+    _pool: CpuPool,
+}
+
+/// A scope for spawning futures which borrow from the stack onto a `CpuPool`.
+///
+/// This is created by `CpuPool::scope`, which waits for all the futures
+/// spawned through it to finish before returning.
+pub struct Scope<'a> {
+    pool: CpuPool,
+    state: Arc<ScopeState>,
+    // Invariant over 'a so that it can't be shortened to fit shorter borrows
+    _marker: PhantomData<&'a mut &'a ()>,
+}
+
+struct ScopeState {
+    // Number of spawned futures which haven't been dropped yet
+    pending: Mutex<usize>,
+    done: Condvar,
+
+    // The first panic raised by a spawned future
+    panic: Mutex<Option<Box<Any + Send>>>,
+}
+
+// A future spawned through a `Scope`, with its lifetime erased. The scope
+// waits for `_guard` to be dropped, which happens only after `fut`.
+struct ScopedTask {
+    fut: CatchUnwind<AssertUnwindSafe<Box<Future<Item = (), Error = ()> + Send>>>,
+    state: Arc<ScopeState>,
+    _guard: ScopeGuard,
+}
+
+struct ScopeGuard {
+    state: Arc<ScopeState>,
 }
 
+/// Error returned by `CpuPool::try_spawn` once the pool has been shut down.
+///
+/// The future that failed to be spawned is returned along with the error.
+pub struct SpawnError<F>(F);
+
 impl CpuPool {
     /// Creates a new thread pool with `size` worker threads associated with it.
     ///
@@ -169,11 +415,43 @@ impl CpuPool {
     /// If the returned future is dropped then this `CpuPool` will attempt to
     /// cancel the computation, if possible. That is, if the computation is in
     /// the middle of working, it will be interrupted when possible.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the pool has been shut down. Use
+    /// `try_spawn` to handle that case instead.
     pub fn spawn<F>(&self, f: F) -> CpuFuture<F::Item, F::Error>
         where F: Future + Send + 'static,
               F::Item: Send + 'static,
               F::Error: Send + 'static,
     {
+        match self.try_spawn(f) {
+            Ok(future) => future,
+            Err(_) => panic!("cannot spawn on a CpuPool which has been shut down"),
+        }
+    }
+
+    /// Spawns a future to run on this thread pool, unless the pool has been
+    /// shut down.
+    ///
+    /// This behaves like `spawn`, except that once `shutdown` or
+    /// `shutdown_now` has been called an error is returned along with the
+    /// future `f`.
+    pub fn try_spawn<F>(&self, f: F) -> Result<CpuFuture<F::Item, F::Error>, SpawnError<F>>
+        where F: Future + Send + 'static,
+              F::Item: Send + 'static,
+              F::Error: Send + 'static,
+    {
+        // Count the task before checking for shutdown, so that `shutdown`
+        // either sees it as active or we see the pool as shut down.
+        self.inner.active.fetch_add(1, Ordering::SeqCst);
+        let guard = TaskGuard { inner: self.inner.clone() };
+        if self.inner.shutdown.load(Ordering::SeqCst) {
+            drop(guard);
+            return Err(SpawnError(f))
+        }
+
+        self.inner.spawned.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = channel();
         let keep_running_flag = Arc::new(AtomicBool::new(false));
         // AssertUnwindSafe is used here becuase `Send + 'static` is basically
@@ -183,9 +461,10 @@ impl CpuPool {
             fut: AssertUnwindSafe(f).catch_unwind(),
             tx: Some(tx),
             keep_running_flag: keep_running_flag.clone(),
+            guard: guard,
         };
         executor::spawn(sender).execute(self.inner.clone());
-        CpuFuture { inner: rx , keep_running_flag: keep_running_flag.clone() }
+        Ok(CpuFuture { inner: rx , keep_running_flag: keep_running_flag.clone() })
     }
 
     /// Spawns a closure on this thread pool.
@@ -206,19 +485,521 @@ impl CpuPool {
     {
         self.spawn(lazy(f))
     }
+
+    /// Creates a scope for spawning futures which borrow local data.
+    ///
+    /// The closure `f` is passed a `Scope`, which can spawn futures onto this
+    /// thread pool without requiring them to be `'static`. This function
+    /// blocks until all of the futures spawned through the scope have finished,
+    /// so any data they borrow outlives them.
+    ///
+    /// If `f` or any of the spawned futures panic then, once all the futures
+    /// have finished, this function panics with the same payload. A panic in
+    /// `f` takes precedence.
+    ///
+    /// Note that this blocks the calling thread, so calling it from a future
+    /// running on this same pool can deadlock if all the workers are busy.
+    ///
+    /// ```rust
+    /// extern crate futures;
+    /// extern crate futures_cpupool;
+    ///
+    /// use futures::future;
+    /// use futures_cpupool::CpuPool;
+    ///
+    /// # fn main() {
+    /// let pool = CpuPool::new(4);
+    /// let data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    /// let mut sums = vec![0; 4];
+    ///
+    /// pool.scope(|s| {
+    ///     for (chunk, sum) in data.chunks(2).zip(sums.iter_mut()) {
+    ///         s.spawn(future::lazy(move || {
+    ///             *sum = chunk.iter().sum();
+    ///             Ok(())
+    ///         }));
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(sums, [3, 7, 11, 15]);
+    /// # }
+    /// ```
+    pub fn scope<'a, F, R>(&self, f: F) -> R
+        where F: FnOnce(&Scope<'a>) -> R
+    {
+        let scope = Scope {
+            pool: self.clone(),
+            state: Arc::new(ScopeState {
+                pending: Mutex::new(0),
+                done: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            _marker: PhantomData,
+        };
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        let mut pending = scope.state.pending.lock().unwrap();
+        while *pending > 0 {
+            pending = scope.state.done.wait(pending).unwrap();
+        }
+        drop(pending);
+
+        let res = match res {
+            Ok(res) => res,
+            Err(e) => panic::resume_unwind(e),
+        };
+        if let Some(e) = scope.state.panic.lock().unwrap().take() {
+            panic::resume_unwind(e);
+        }
+        res
+    }
+
+    /// Returns a snapshot of this thread pool's runtime statistics.
+    ///
+    /// This can be used to tell whether the pool is keeping up with the work
+    /// spawned onto it, for example by looking at how much work is queued and
+    /// how much of their time the workers spend running tasks.
+    pub fn metrics(&self) -> Metrics {
+        let inner = &self.inner;
+        Metrics {
+            queue_depth: inner.queued(),
+            tasks_spawned: inner.spawned.load(Ordering::SeqCst),
+            tasks_completed: inner.completed.load(Ordering::SeqCst),
+            tasks_panicked: inner.panicked.load(Ordering::SeqCst),
+            threads: inner.num_threads.load(Ordering::SeqCst),
+            workers: {
+                let slots = inner.slots.load();
+                (0..slots.len())
+                    .map(|i| slots.get(i).stats.lock().unwrap().clone())
+                    .collect()
+            },
+        }
+    }
+
+    /// Shuts down this thread pool once all of its work has finished.
+    ///
+    /// No more futures can be spawned onto the pool after this is called, from
+    /// this handle or any of its clones, but futures which have already been
+    /// spawned run to completion as usual. The returned future resolves once
+    /// they have all finished, at which point the worker threads exit.
+    pub fn shutdown(&self) -> Shutdown {
+        self.inner.shutdown.store(true, Ordering::SeqCst);
+        self.wait_shutdown()
+    }
+
+    /// Shuts down this thread pool, dropping all work that isn't running.
+    ///
+    /// As with `shutdown`, no more futures can be spawned onto the pool after
+    /// this is called. In addition, futures which are waiting to run are
+    /// dropped instead, which cancels their `CpuFuture`s (see
+    /// `CpuFuture::catch_cancel`). Futures which are blocked waiting for an
+    /// event are dropped once that event occurs, and futures in the middle of
+    /// being polled are dropped once that poll returns.
+    ///
+    /// The returned future resolves once all tasks have been dropped and the
+    /// worker threads exit.
+    pub fn shutdown_now(&self) -> Shutdown {
+        self.inner.abort.store(true, Ordering::SeqCst);
+        self.inner.shutdown.store(true, Ordering::SeqCst);
+
+        // Take as much queued work as we can find and drop it here. Anything
+        // we miss, like tasks being unparked right now or in the workers' LIFO
+        // slots, is dropped by the workers.
+        let mut runs = Vec::new();
+        while let Some(run) = self.inner.pop_injector() {
+            runs.push(run);
+        }
+        let slots = self.inner.slots.load();
+        for i in 0..slots.len() {
+            while let Some(run) = slots.get(i).steal() {
+                runs.push(run);
+            }
+        }
+        drop(runs);
+
+        self.wait_shutdown()
+    }
+
+    fn wait_shutdown(&self) -> Shutdown {
+        let (tx, rx) = channel();
+        self.inner.shutdown_waiters.lock().unwrap().push(tx);
+        if self.inner.active.load(Ordering::SeqCst) == 0 {
+            self.inner.finish_shutdown();
+        }
+        Shutdown { inner: rx, _pool: self.clone() }
+    }
 }
 
-fn work(inner: &Inner) {
+fn work(inner: &Inner, index: usize, slot: Arc<Slot>, deque: chase_lev::Worker<Run>) {
     inner.after_start.as_ref().map(|fun| fun());
+    CURRENT.with(|cur| {
+        *cur.borrow_mut() = Some(WorkerContext {
+            pool: inner,
+            index: index,
+            slot: slot.clone(),
+            deque: deque,
+            lifo: None,
+            lifo_streak: 0,
+            tick: 0,
+        });
+    });
     loop {
-        match inner.queue.pop() {
-            Message::Run(r) => r.run(),
-            Message::Close => break,
+        match inner.next_task() {
+            Some(run) => {
+                // If there's more work queued behind this task, make sure
+                // another worker is on its way to take it.
+                if slot.queued.load(Ordering::SeqCst) > 0 ||
+                   inner.injected.load(Ordering::SeqCst) > 0 {
+                    inner.notify_one();
+                }
+                if inner.abort.load(Ordering::SeqCst) {
+                    drop(run);
+                } else {
+                    let start = Instant::now();
+                    run.run();
+                    let elapsed = start.elapsed();
+                    {
+                        let mut stats = slot.stats.lock().unwrap();
+                        stats.busy_time += elapsed;
+                        stats.polls += 1;
+                    }
+                    inner.after_poll.as_ref().map(|fun| fun(elapsed));
+                }
+            }
+            None => {
+                if !inner.sleep() {
+                    break
+                }
+            }
         }
     }
+    // Our deque is empty by now, so hand it back for the next worker started
+    // in our place
+    let cx = CURRENT.with(|cur| cur.borrow_mut().take()).unwrap();
+    *slot.idle_deque.lock().unwrap() = Some(cx.deque);
+    // Work queued just as we decided to stop may not have been able to start
+    // a new worker while we held on to the deque, so try again on its behalf.
+    if inner.queued() > 0 {
+        inner.grow();
+    }
     inner.before_stop.as_ref().map(|fun| fun());
 }
 
+fn spawn_worker(inner: Arc<Inner>, index: usize, slot: Arc<Slot>,
+                deque: chase_lev::Worker<Run>) {
+    let mut thread_builder = thread::Builder::new();
+    if let Some(ref name_prefix) = inner.name_prefix {
+        thread_builder = thread_builder.name(format!("{}{}", name_prefix, index));
+    }
+    thread_builder.spawn(move || work(&inner, index, slot, deque)).unwrap();
+}
+
+impl Inner {
+    // Finds the next task for the current worker thread to run, looking at
+    // the LIFO slot, the worker's own deque, the injector and then the other
+    // workers' slots in turn.
+    fn next_task(&self) -> Option<Run> {
+        CURRENT.with(|cur| {
+            let mut cur = cur.borrow_mut();
+            let cx = cur.as_mut().expect("not a worker thread");
+            cx.tick = cx.tick.wrapping_add(1);
+            if cx.lifo_streak < LIFO_LIMIT {
+                if let Some(run) = cx.lifo.take() {
+                    cx.lifo_streak += 1;
+                    return Some(run)
+                }
+            }
+            cx.lifo_streak = 0;
+            if cx.tick % INJECTOR_INTERVAL == 0 {
+                if let Some(run) = self.pop_injector() {
+                    return Some(run)
+                }
+            }
+            if let Some(run) = cx.deque.try_pop() {
+                cx.slot.queued.fetch_sub(1, Ordering::SeqCst);
+                return Some(run)
+            }
+            self.pop_injector()
+                .or_else(|| self.steal(cx.index))
+                // Nothing else to do, so fall back to the LIFO slot if it was
+                // skipped above.
+                .or_else(|| cx.lifo.take())
+        })
+    }
+
+    fn pop_injector(&self) -> Option<Run> {
+        if self.injected.load(Ordering::SeqCst) == 0 {
+            return None
+        }
+        let run = self.injector.try_pop().and_then(|run| run);
+        if run.is_some() {
+            self.injected.fetch_sub(1, Ordering::SeqCst);
+        }
+        run
+    }
+
+    fn steal(&self, index: usize) -> Option<Run> {
+        let slots = self.slots.load();
+        let n = slots.len();
+        for i in 1..n {
+            let victim = slots.get((index + i) % n);
+            // Leave the deques of workers with nothing queued alone
+            if victim.queued.load(Ordering::SeqCst) == 0 {
+                continue
+            }
+            if let Some(run) = victim.steal() {
+                return Some(run)
+            }
+        }
+        None
+    }
+
+    // Number of tasks waiting to run in the injector or in one of the workers'
+    // deques, which leaves out the LIFO slots as only their owners run them.
+    fn queued(&self) -> usize {
+        let slots = self.slots.load();
+        (0..slots.len()).fold(self.injected.load(Ordering::SeqCst), |n, i| {
+            n + slots.get(i).queued.load(Ordering::SeqCst)
+        })
+    }
+
+    // Blocks the current worker until there may be more work to do. Returns
+    // `false` if the worker should exit instead, either as the pool has been
+    // shut down and all queued work has been taken, or as the worker has been
+    // idle for longer than the keep-alive and isn't one of the minimum number
+    // of threads.
+    fn sleep(&self) -> bool {
+        let lock = self.sleep_lock.lock().unwrap();
+        // Announce that we're about to sleep before checking for work, which
+        // pairs with submitters bumping a `queued` counter before checking
+        // `idle`.
+        self.idle.fetch_add(1, Ordering::SeqCst);
+        let timed_out = if self.queued() > 0 {
+            false
+        } else if self.closed.load(Ordering::SeqCst) {
+            self.wake_up();
+            self.num_threads.fetch_sub(1, Ordering::SeqCst);
+            return false
+        } else if self.num_threads.load(Ordering::SeqCst) > self.min_threads {
+            let (_lock, res) = self.sleep_cvar.wait_timeout(lock, self.keep_alive).unwrap();
+            res.timed_out()
+        } else {
+            drop(self.sleep_cvar.wait(lock).unwrap());
+            false
+        };
+        // If we were picked to wake up there's work to look for, even if the
+        // keep-alive ran out at the same time.
+        let notified = self.wake_up();
+        !(timed_out && !notified && self.retire())
+    }
+
+    // Stops counting the current worker as idle, returning whether it had
+    // been picked to wake up. Workers can't tell which of them was picked, so
+    // any one of them takes the place of the one that was, which leaves the
+    // counts the same either way.
+    fn wake_up(&self) -> bool {
+        let mut n = self.idle.load(Ordering::SeqCst);
+        loop {
+            let (next, notified) = if n >= IDLE_NOTIFIED {
+                (n - IDLE_NOTIFIED, true)
+            } else {
+                (n - 1, false)
+            };
+            match self.idle.compare_exchange(n, next, Ordering::SeqCst,
+                                             Ordering::SeqCst) {
+                Ok(_) => return notified,
+                Err(cur) => n = cur,
+            }
+        }
+    }
+
+    // Stops counting the current worker as running if there are more than the
+    // minimum number of workers, returning whether it should exit.
+    fn retire(&self) -> bool {
+        let mut n = self.num_threads.load(Ordering::SeqCst);
+        loop {
+            if n <= self.min_threads {
+                return false
+            }
+            match self.num_threads.compare_exchange(n, n - 1, Ordering::SeqCst,
+                                                    Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(cur) => n = cur,
+            }
+        }
+        // Work may have been queued as we stopped counting as a sleeper, in
+        // which case its submitter may have notified us rather than starting
+        // a new worker, so stick around to run it.
+        if self.queued() > 0 {
+            self.num_threads.fetch_add(1, Ordering::SeqCst);
+            return false
+        }
+        true
+    }
+
+    // Wakes up a sleeping worker to run newly queued work, or starts a new
+    // worker if they're all busy and there's room for another.
+    fn notify_one(&self) {
+        // A worker which is already waking up passes on any more work it
+        // finds queued once it takes a task, so only pick a sleeper to wake
+        // if there isn't one. This way the lock is only taken when there's
+        // somebody to wake.
+        let mut n = self.idle.load(Ordering::SeqCst);
+        loop {
+            if n >= IDLE_NOTIFIED {
+                return
+            }
+            if n == 0 {
+                return self.grow()
+            }
+            match self.idle.compare_exchange(n, n - 1 + IDLE_NOTIFIED,
+                                             Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(cur) => n = cur,
+            }
+        }
+        let _lock = self.sleep_lock.lock().unwrap();
+        self.sleep_cvar.notify_one();
+    }
+
+    fn grow(&self) {
+        let mut n = self.num_threads.load(Ordering::SeqCst);
+        loop {
+            if n >= self.max_threads || self.closed.load(Ordering::SeqCst) {
+                return
+            }
+            match self.num_threads.compare_exchange(n, n + 1, Ordering::SeqCst,
+                                                    Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(cur) => n = cur,
+            }
+        }
+
+        // A worker which is just exiting may not have handed its deque back
+        // yet, in which case we give up and leave the work to the others.
+        let slot = self.take_slot();
+        let inner = self.this.lock().unwrap().upgrade();
+        match (slot, inner) {
+            (Some((index, slot, deque)), Some(inner)) => {
+                spawn_worker(inner, index, slot, deque)
+            }
+            (slot, _) => {
+                if let Some((_, slot, deque)) = slot {
+                    *slot.idle_deque.lock().unwrap() = Some(deque);
+                }
+                self.num_threads.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    // Finds a slot for a new worker, reusing one left behind by a worker which
+    // has stopped, or adding one if there's room for more.
+    fn take_slot(&self) -> Option<(usize, Arc<Slot>, chase_lev::Worker<Run>)> {
+        let mut owned = self.slots.owned.lock().unwrap();
+        for (index, slot) in owned.slots.iter().enumerate() {
+            if let Some(deque) = slot.idle_deque.lock().unwrap().take() {
+                return Some((index, slot.clone(), deque))
+            }
+        }
+        if owned.slots.len() >= self.max_threads {
+            return None
+        }
+        let (deque, stealer) = chase_lev::deque();
+        let slot = Arc::new(Slot {
+            stealer: stealer,
+            queued: AtomicUsize::new(0),
+            idle_deque: Mutex::new(None),
+            stats: Mutex::new(WorkerMetrics::default()),
+        });
+        let index = self.slots.add(&mut owned, slot.clone());
+        Some((index, slot, deque))
+    }
+
+    fn finish_shutdown(&self) {
+        let waiters = mem::replace(&mut *self.shutdown_waiters.lock().unwrap(), Vec::new());
+        for tx in waiters {
+            tx.complete(());
+        }
+        self.close();
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        let _lock = self.sleep_lock.lock().unwrap();
+        self.sleep_cvar.notify_all();
+    }
+}
+
+impl Slot {
+    // Takes a task from the worker's deque from another thread
+    fn steal(&self) -> Option<Run> {
+        loop {
+            match self.stealer.steal() {
+                Steal::Data(run) => {
+                    self.queued.fetch_sub(1, Ordering::SeqCst);
+                    return Some(run)
+                }
+                Steal::Abort => {}
+                Steal::Empty => return None,
+            }
+        }
+    }
+}
+
+impl Slots {
+    fn new() -> Slots {
+        let mut table = Vec::new();
+        Slots {
+            len: AtomicUsize::new(0),
+            table: AtomicPtr::new(table.as_mut_ptr()),
+            owned: Mutex::new(OwnedSlots {
+                slots: Vec::new(),
+                tables: vec![table],
+            }),
+        }
+    }
+
+    fn load<'a>(&'a self) -> SlotsRef<'a> {
+        // Pairs with the release stores in `add`, so that the table loaded
+        // next holds at least `len` slots.
+        let len = self.len.load(Ordering::Acquire);
+        let table = self.table.load(Ordering::Acquire);
+        SlotsRef { table: unsafe { slice::from_raw_parts(table, len) } }
+    }
+
+    // Adds a slot, returning its index. `owned` is the locked `self.owned`.
+    fn add(&self, owned: &mut OwnedSlots, slot: Arc<Slot>) -> usize {
+        let index = owned.slots.len();
+        owned.slots.push(slot);
+        if index == owned.tables.last().unwrap().len() {
+            let mut table = (0..cmp::max(index * 2, 4)).map(|i| {
+                AtomicPtr::new(match owned.slots.get(i) {
+                    Some(slot) => &**slot as *const Slot as *mut Slot,
+                    None => ptr::null_mut(),
+                })
+            }).collect::<Vec<_>>();
+            self.table.store(table.as_mut_ptr(), Ordering::Release);
+            owned.tables.push(table);
+        } else {
+            let slot = &*owned.slots[index] as *const Slot as *mut Slot;
+            owned.tables.last().unwrap()[index].store(slot, Ordering::Relaxed);
+        }
+        self.len.store(index + 1, Ordering::Release);
+        index
+    }
+}
+
+impl<'a> SlotsRef<'a> {
+    fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    fn get(&self, index: usize) -> &'a Slot {
+        // Slots are never removed, and live as long as the table
+        unsafe { &*self.table[index].load(Ordering::Relaxed) }
+    }
+}
+
 impl Clone for CpuPool {
     fn clone(&self) -> CpuPool {
         self.inner.cnt.fetch_add(1, Ordering::Relaxed);
@@ -229,16 +1010,54 @@ impl Clone for CpuPool {
 impl Drop for CpuPool {
     fn drop(&mut self) {
         if self.inner.cnt.fetch_sub(1, Ordering::Relaxed) == 1 {
-            for _ in 0..self.inner.size {
-                self.inner.queue.push(Message::Close);
-            }
+            self.inner.close();
         }
     }
 }
 
 impl Executor for Inner {
     fn execute(&self, run: Run) {
-        self.queue.push(Message::Run(run))
+        if self.abort.load(Ordering::SeqCst) {
+            return drop(run)
+        }
+
+        // Tasks unparked on one of our own workers go into its LIFO slot,
+        // pushing the previous occupant onto the worker's deque. Only the
+        // worker itself runs the task in its LIFO slot, so we only need to
+        // let the others know if something was pushed onto the deque.
+        let local = CURRENT.with(|cur| {
+            let mut cur = match cur.try_borrow_mut() {
+                Ok(cur) => cur,
+                Err(_) => return Err(run),
+            };
+            match *cur {
+                Some(ref mut cx) if ptr::eq(cx.pool, self) => {
+                    let prev = match cx.lifo.take() {
+                        Some(prev) => prev,
+                        None => {
+                            cx.lifo = Some(run);
+                            return Ok(false)
+                        }
+                    };
+                    cx.lifo = Some(run);
+                    cx.slot.queued.fetch_add(1, Ordering::SeqCst);
+                    cx.deque.push(prev);
+                    Ok(true)
+                }
+                _ => Err(run),
+            }
+        });
+        let notify = match local {
+            Ok(pushed) => pushed,
+            Err(run) => {
+                self.injected.fetch_add(1, Ordering::SeqCst);
+                self.injector.push(Some(run));
+                true
+            }
+        };
+        if notify {
+            self.notify_one();
+        }
     }
 }
 
@@ -251,6 +1070,27 @@ impl<T, E> CpuFuture<T, E> {
     pub fn forget(self) {
         self.keep_running_flag.store(true, Ordering::SeqCst);
     }
+
+    /// Reports the cancellation of the underlying future as an error.
+    ///
+    /// Futures which were waiting to run when `CpuPool::shutdown_now` was
+    /// called are dropped without completing, in which case this `CpuFuture`
+    /// panics when polled. The returned future fails with
+    /// `CancelError::Canceled` instead, and wraps any other error in
+    /// `CancelError::Failed`.
+    pub fn catch_cancel(self) -> CatchCancel<T, E> {
+        CatchCancel { inner: self }
+    }
+
+    fn poll_catch_cancel(&mut self) -> Poll<T, CancelError<E>> {
+        match self.inner.poll() {
+            Ok(Async::Ready(Ok(Ok(e)))) => Ok(e.into()),
+            Ok(Async::Ready(Ok(Err(e)))) => Err(CancelError::Failed(e)),
+            Ok(Async::Ready(Err(e))) => panic::resume_unwind(e),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(_) => Err(CancelError::Canceled),
+        }
+    }
 }
 
 impl<T: Send + 'static, E: Send + 'static> Future for CpuFuture<T, E> {
@@ -258,15 +1098,207 @@ impl<T: Send + 'static, E: Send + 'static> Future for CpuFuture<T, E> {
     type Error = E;
 
     fn poll(&mut self) -> Poll<T, E> {
-        match self.inner.poll().expect("shouldn't be canceled") {
-            Async::Ready(Ok(Ok(e))) => Ok(e.into()),
-            Async::Ready(Ok(Err(e))) => Err(e),
-            Async::Ready(Err(e)) => panic::resume_unwind(e),
-            Async::NotReady => Ok(Async::NotReady),
+        match self.poll_catch_cancel() {
+            Ok(res) => Ok(res),
+            Err(CancelError::Failed(e)) => Err(e),
+            Err(CancelError::Canceled) => {
+                panic!("future was dropped by CpuPool::shutdown_now before \
+                        completing, use `catch_cancel` to handle this")
+            }
+        }
+    }
+}
+
+impl<T: Send + 'static, E: Send + 'static> Future for CatchCancel<T, E> {
+    type Item = T;
+    type Error = CancelError<E>;
+
+    fn poll(&mut self) -> Poll<T, CancelError<E>> {
+        self.inner.poll_catch_cancel()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CancelError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CancelError::Canceled => write!(fmt, "future was canceled by a pool shutdown"),
+            CancelError::Failed(ref e) => e.fmt(fmt),
+        }
+    }
+}
+
+impl<E: Error> Error for CancelError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            CancelError::Canceled => "future was canceled by a pool shutdown",
+            CancelError::Failed(_) => "spawned future failed",
+        }
+    }
+}
+
+impl<'a> Scope<'a> {
+    /// Spawns a future onto the thread pool which may borrow data living for
+    /// `'a`.
+    ///
+    /// The future's result is discarded, so any output should be written to
+    /// borrowed data instead. Errors are ignored, while panics are propagated
+    /// by `CpuPool::scope` once all futures have finished.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the pool has been shut down.
+    pub fn spawn<F>(&self, f: F)
+        where F: Future<Item = (), Error = ()> + Send + 'a
+    {
+        *self.state.pending.lock().unwrap() += 1;
+        let guard = ScopeGuard { state: self.state.clone() };
+
+        let fut: Box<Future<Item = (), Error = ()> + Send + 'a> = Box::new(f);
+        // This is safe as `CpuPool::scope` doesn't return until the guard, and
+        // therefore the future, has been dropped. Nothing borrowed for 'a is
+        // accessed past that point.
+        let fut: Box<Future<Item = (), Error = ()> + Send> = unsafe {
+            mem::transmute(fut)
+        };
+        let task = ScopedTask {
+            fut: AssertUnwindSafe(fut).catch_unwind(),
+            state: self.state.clone(),
+            _guard: guard,
+        };
+        self.pool.spawn(task).forget();
+    }
+}
+
+impl Future for ScopedTask {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        match self.fut.poll() {
+            Ok(Async::Ready(_)) => Ok(Async::Ready(())),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(e) => {
+                let mut panic = self.state.panic.lock().unwrap();
+                if panic.is_none() {
+                    *panic = Some(e);
+                }
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let mut pending = self.state.pending.lock().unwrap();
+        *pending -= 1;
+        if *pending == 0 {
+            self.state.done.notify_all();
         }
     }
 }
 
+impl Metrics {
+    /// Returns the number of tasks waiting to run.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Returns the number of futures spawned onto the pool.
+    pub fn tasks_spawned(&self) -> usize {
+        self.tasks_spawned
+    }
+
+    /// Returns the number of spawned futures which have resolved, either
+    /// successfully or with an error.
+    pub fn tasks_completed(&self) -> usize {
+        self.tasks_completed
+    }
+
+    /// Returns the number of spawned futures which panicked.
+    pub fn tasks_panicked(&self) -> usize {
+        self.tasks_panicked
+    }
+
+    /// Returns the number of worker threads currently running.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns statistics for each of the pool's worker threads.
+    ///
+    /// There's an entry for each worker thread started, up to the most that
+    /// have run at once. When a worker thread stops its entry is kept, and
+    /// carries on being updated by the next worker started in its place.
+    pub fn workers(&self) -> &[WorkerMetrics] {
+        &self.workers
+    }
+}
+
+impl WorkerMetrics {
+    /// Returns the total time this worker has spent running tasks.
+    pub fn busy_time(&self) -> Duration {
+        self.busy_time
+    }
+
+    /// Returns the number of times this worker has run a task.
+    ///
+    /// A task may be polled several times in one run if it's unparked while
+    /// being polled.
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.inner.active.fetch_sub(1, Ordering::SeqCst) == 1 &&
+           self.inner.shutdown.load(Ordering::SeqCst) {
+            self.inner.finish_shutdown();
+        }
+    }
+}
+
+impl Future for Shutdown {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        // The sender is only dropped once it's been completed, as the pool
+        // handle we hold keeps it alive.
+        self.inner.poll().map_err(|_| ())
+    }
+}
+
+impl<F> fmt::Debug for SpawnError<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("SpawnError")
+            .field(&"...")
+            .finish()
+    }
+}
+
+impl<F> fmt::Display for SpawnError<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "spawn failed because the pool has been shut down")
+    }
+}
+
+impl<F> Error for SpawnError<F>
+    where F: ::std::any::Any
+{
+    fn description(&self) -> &str {
+        "spawn failed because the pool has been shut down"
+    }
+}
+
+impl<F> SpawnError<F> {
+    /// Returns the future that was attempted to be spawned but failed.
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
 impl<F: Future> Future for MySender<F, Result<F::Item, F::Error>> {
     type Item = ();
     type Error = ();
@@ -280,9 +1312,15 @@ impl<F: Future> Future for MySender<F, Result<F::Item, F::Error>> {
         }
 
         let res = match self.fut.poll() {
-            Ok(Async::Ready(e)) => Ok(e),
+            Ok(Async::Ready(e)) => {
+                self.guard.inner.completed.fetch_add(1, Ordering::SeqCst);
+                Ok(e)
+            }
             Ok(Async::NotReady) => return Ok(Async::NotReady),
-            Err(e) => Err(e),
+            Err(e) => {
+                self.guard.inner.panicked.fetch_add(1, Ordering::SeqCst);
+                Err(e)
+            }
         };
         self.tx.take().unwrap().complete(res);
         Ok(Async::Ready(()))
@@ -294,18 +1332,58 @@ impl Builder {
     /// of CPUs on the host.
     pub fn new() -> Builder {
         Builder {
-            pool_size: num_cpus::get(),
+            min_threads: None,
+            max_threads: None,
+            keep_alive: Duration::from_secs(60),
             name_prefix: None,
             after_start: None,
             before_stop: None,
+            after_poll: None,
         }
     }
 
     /// Set size of a future CpuPool
     ///
-    /// The size of a thread pool is the number of worker threads spawned
+    /// The size of a thread pool is the number of worker threads spawned.
+    /// This sets both the minimum and maximum number of threads to `size`.
     pub fn pool_size(&mut self, size: usize) -> &mut Self {
-        self.pool_size = size;
+        self.min_threads = Some(size);
+        self.max_threads = Some(size);
+        self
+    }
+
+    /// Set the minimum number of worker threads of a future CpuPool
+    ///
+    /// This many workers are started when the pool is created and kept
+    /// running until the pool shuts down, however idle they are. It may be
+    /// zero, in which case a worker is only started once there's work to do.
+    ///
+    /// Unless the maximum number of threads is also set, it's raised to `min`
+    /// if it would otherwise be lower.
+    pub fn min_threads(&mut self, min: usize) -> &mut Self {
+        self.min_threads = Some(min);
+        self
+    }
+
+    /// Set the maximum number of worker threads of a future CpuPool
+    ///
+    /// When work is spawned while all workers are busy, another worker is
+    /// started, up to this many in total. Workers beyond the minimum number
+    /// stop again after being idle for the `keep_alive` duration.
+    ///
+    /// Unless the minimum number of threads is also set, it's lowered to
+    /// `max` if it would otherwise be higher.
+    pub fn max_threads(&mut self, max: usize) -> &mut Self {
+        self.max_threads = Some(max);
+        self
+    }
+
+    /// Set how long workers beyond the minimum number may stay idle before
+    /// they stop
+    ///
+    /// This defaults to 60 seconds.
+    pub fn keep_alive(&mut self, dur: Duration) -> &mut Self {
+        self.keep_alive = dur;
         self
     }
 
@@ -321,7 +1399,8 @@ impl Builder {
     /// Execute function `f` right after each thread is started but before
     /// running any jobs on it
     ///
-    /// This is initially intended for bookkeeping and monitoring uses
+    /// This includes workers started later on when the pool grows. This is
+    /// initially intended for bookkeeping and monitoring uses
     pub fn after_start<F>(&mut self, f: F) -> &mut Self
         where F: Fn() + Send + Sync + 'static
     {
@@ -331,7 +1410,8 @@ impl Builder {
 
     /// Execute function `f` before each worker thread stops
     ///
-    /// This is initially intended for bookkeeping and monitoring uses
+    /// This includes workers stopping after being idle for too long. This is
+    /// initially intended for bookkeeping and monitoring uses
     pub fn before_stop<F>(&mut self, f: F) -> &mut Self
         where F: Fn() + Send + Sync + 'static
     {
@@ -339,26 +1419,63 @@ impl Builder {
         self
     }
 
+    /// Execute function `f` each time a worker thread has polled a task,
+    /// passing in how long the poll took
+    ///
+    /// This is intended for monitoring uses, such as recording a histogram of
+    /// poll durations. It runs on the worker thread, so it should be cheap.
+    pub fn after_poll<F>(&mut self, f: F) -> &mut Self
+        where F: Fn(Duration) + Send + Sync + 'static
+    {
+        self.after_poll = Some(Arc::new(f));
+        self
+    }
+
     /// Create CpuPool with configured parameters
     pub fn create(&mut self) -> CpuPool {
+        let num_cpus = num_cpus::get();
+        let (min_threads, max_threads) = match (self.min_threads, self.max_threads) {
+            (Some(min), Some(max)) => (min, max),
+            (Some(min), None) => (min, cmp::max(min, num_cpus)),
+            (None, Some(max)) => (cmp::min(max, num_cpus), max),
+            (None, None) => (num_cpus, num_cpus),
+        };
+        assert!(max_threads > 0);
+        assert!(min_threads <= max_threads,
+                "min_threads must not be greater than max_threads");
         let pool = CpuPool {
             inner: Arc::new(Inner {
-                queue: MsQueue::new(),
+                injector: MsQueue::new(),
+                injected: AtomicUsize::new(0),
+                slots: Slots::new(),
+                num_threads: AtomicUsize::new(min_threads),
+                min_threads: min_threads,
+                max_threads: max_threads,
+                keep_alive: self.keep_alive,
+                this: Mutex::new(Weak::new()),
+                active: AtomicUsize::new(0),
+                shutdown: AtomicBool::new(false),
+                abort: AtomicBool::new(false),
+                shutdown_waiters: Mutex::new(Vec::new()),
+                spawned: AtomicUsize::new(0),
+                completed: AtomicUsize::new(0),
+                panicked: AtomicUsize::new(0),
+                idle: AtomicUsize::new(0),
+                sleep_lock: Mutex::new(()),
+                sleep_cvar: Condvar::new(),
+                closed: AtomicBool::new(false),
                 cnt: AtomicUsize::new(1),
-                size: self.pool_size,
                 after_start: self.after_start.clone(),
                 before_stop: self.before_stop.clone(),
+                after_poll: self.after_poll.clone(),
+                name_prefix: self.name_prefix.clone(),
             }),
         };
-        assert!(self.pool_size > 0);
+        *pool.inner.this.lock().unwrap() = Arc::downgrade(&pool.inner);
 
-        for counter in 0..self.pool_size {
-            let inner = pool.inner.clone();
-            let mut thread_builder = thread::Builder::new();
-            if let Some(ref name_prefix) = self.name_prefix {
-                thread_builder = thread_builder.name(format!("{}{}", name_prefix, counter));
-            }
-            thread_builder.spawn(move || work(&inner)).unwrap();
+        for _ in 0..min_threads {
+            let (index, slot, deque) = pool.inner.take_slot().unwrap();
+            spawn_worker(pool.inner.clone(), index, slot, deque);
         }
 
         return pool
@@ -1,12 +1,16 @@
 extern crate futures;
 extern crate futures_cpupool;
 
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, mpsc as std_mpsc};
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 use std::thread;
 use std::time::Duration;
 
-use futures::future::{Future, BoxFuture};
-use futures_cpupool::{CpuPool, Builder};
+use futures::future::{self, Future, BoxFuture};
+use futures::stream::Stream;
+use futures::sync::{mpsc, oneshot};
+use futures_cpupool::{CpuPool, CpuFuture, Builder, CancelError};
 
 fn done<T: Send + 'static>(t: T) -> BoxFuture<T, ()> {
     futures::future::ok(t).boxed()
@@ -108,3 +112,395 @@ fn thread_name() {
     });
     let _ = future.wait();
 }
+
+#[test]
+fn spawn_from_worker() {
+    let pool = CpuPool::new(2);
+    let pool2 = pool.clone();
+    let sum = pool.spawn_fn(move || {
+        let futures = (0..100).map(|i| pool2.spawn(done(i))).collect::<Vec<_>>();
+        future::join_all(futures).map(|v| v.into_iter().sum::<i32>())
+    });
+    assert_eq!(sum.wait(), Ok(4950));
+}
+
+#[test]
+fn idle_workers_steal() {
+    let pool = CpuPool::new(4);
+    let pool2 = pool.clone();
+    // All of these are queued on the spawning worker, so any that run
+    // elsewhere must have been stolen.
+    let ids = pool.spawn_fn(move || {
+        let futures = (0..8).map(|_| {
+            pool2.spawn_fn(|| {
+                thread::sleep(Duration::from_millis(50));
+                Ok::<_, ()>(thread::current().id())
+            })
+        }).collect::<Vec<_>>();
+        future::join_all(futures)
+    }).wait().unwrap();
+
+    let mut distinct = ids.clone();
+    distinct.dedup();
+    assert!(distinct.len() > 1);
+}
+
+#[test]
+fn lifo_slot_does_not_starve() {
+    const N: usize = 10_000;
+    let pool = CpuPool::new(1);
+    let pool2 = pool.clone();
+    let rounds = Arc::new(AtomicUsize::new(0));
+    let rounds2 = rounds.clone();
+
+    let seen = pool.spawn_fn(move || {
+        let other = pool2.spawn_fn(move || {
+            Ok::<_, ()>(rounds2.load(Ordering::SeqCst))
+        });
+
+        // A pair of tasks which keep unparking each other, and so keep
+        // taking turns in the worker's LIFO slot.
+        let (tx_a, rx_a) = mpsc::unbounded();
+        let (tx_b, rx_b) = mpsc::unbounded();
+        let tx_a2 = tx_a.clone();
+        pool2.spawn(rx_a.take(N as u64).for_each(move |i: usize| {
+            rounds.fetch_add(1, Ordering::SeqCst);
+            tx_b.send(i + 1).map_err(|_| ())
+        })).forget();
+        pool2.spawn(rx_b.for_each(move |i| {
+            tx_a2.send(i).map_err(|_| ())
+        })).forget();
+        tx_a.send(0).unwrap();
+
+        other
+    }).wait().unwrap();
+
+    assert!(seen < N);
+}
+
+#[test]
+fn lifo_slot_stays_on_its_worker() {
+    let pool = CpuPool::new(2);
+    let (polled_tx, polled_rx) = std_mpsc::channel();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let waiter = pool.spawn(future::lazy(move || {
+        polled_tx.send(()).unwrap();
+        rx.map(|()| thread::current().id())
+    }).map_err(|_| ()));
+    polled_rx.recv().unwrap();
+    thread::sleep(Duration::from_millis(20));
+
+    // The waiter is unparked into this worker's LIFO slot, so it waits for
+    // this task to return rather than moving to the idle worker.
+    let unparker = pool.spawn_fn(move || {
+        tx.complete(());
+        thread::sleep(Duration::from_millis(50));
+        Ok::<_, ()>(thread::current().id())
+    });
+    assert_eq!(waiter.wait(), unparker.wait());
+}
+
+#[test]
+fn shutdown_waits_for_tasks() {
+    let pool = CpuPool::new(2);
+    let done = Arc::new(AtomicUsize::new(0));
+    for _ in 0..4 {
+        let done = done.clone();
+        pool.spawn_fn(move || {
+            thread::sleep(Duration::from_millis(20));
+            done.fetch_add(1, Ordering::SeqCst);
+            Ok::<(), ()>(())
+        }).forget();
+    }
+    pool.shutdown().wait().unwrap();
+    assert_eq!(done.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn shutdown_idle_pool() {
+    static NUM_STOPS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let pool = Builder::new()
+        .pool_size(2)
+        .before_stop(|| { NUM_STOPS.fetch_add(1, Ordering::SeqCst); })
+        .create();
+    pool.shutdown().wait().unwrap();
+
+    // Our handle is still alive, but the workers exit anyway
+    for _ in 0..100 {
+        if NUM_STOPS.load(Ordering::SeqCst) == 2 {
+            return
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("threads didn't exit");
+}
+
+#[test]
+fn spawn_after_shutdown() {
+    let pool = CpuPool::new(1);
+    let clone = pool.clone();
+    let shutdown = pool.shutdown();
+    match clone.try_spawn(done(1)) {
+        Err(e) => assert_eq!(e.into_inner().wait(), Ok(1)),
+        Ok(_) => panic!("spawn succeeded"),
+    }
+    shutdown.wait().unwrap();
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| clone.spawn(done(2))));
+    assert!(res.is_err());
+}
+
+#[test]
+fn shutdown_now_drops_pending_tasks() {
+    let pool = CpuPool::new(1);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    // Keep the only worker busy while more work queues up behind it
+    let (started_tx, started_rx) = std_mpsc::channel();
+    let (release_tx, release_rx) = std_mpsc::channel::<()>();
+    let blocker = pool.spawn_fn(move || {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        Ok::<_, ()>(1)
+    });
+    started_rx.recv().unwrap();
+    let pending = (0..3).map(|_| {
+        let ran = ran.clone();
+        pool.spawn_fn(move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+            Ok::<(), ()>(())
+        })
+    }).collect::<Vec<_>>();
+
+    let shutdown = pool.shutdown_now();
+    for future in pending {
+        assert_eq!(future.catch_cancel().wait(), Err(CancelError::Canceled));
+    }
+    release_tx.send(()).unwrap();
+    assert_eq!(blocker.wait(), Ok(1));
+    shutdown.wait().unwrap();
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn metrics() {
+    static NUM_POLLS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let pool = Builder::new()
+        .pool_size(2)
+        .after_poll(|_| { NUM_POLLS.fetch_add(1, Ordering::SeqCst); })
+        .create();
+    let ok = pool.spawn_fn(|| {
+        thread::sleep(Duration::from_millis(20));
+        Ok::<(), ()>(())
+    });
+    let err = pool.spawn(futures::future::err::<(), ()>(()));
+    let panics = pool.spawn_fn(|| -> Result<(), ()> { panic!() });
+    assert_eq!(ok.wait(), Ok(()));
+    assert_eq!(err.wait(), Err(()));
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| panics.wait())).is_err());
+
+    let metrics = pool.metrics();
+    assert_eq!(metrics.tasks_spawned(), 3);
+    assert_eq!(metrics.tasks_completed(), 2);
+    assert_eq!(metrics.tasks_panicked(), 1);
+    assert_eq!(metrics.queue_depth(), 0);
+    assert_eq!(metrics.workers().len(), 2);
+
+    // Per-worker statistics are recorded once each run returns, which may be
+    // after the futures above have resolved.
+    for _ in 0..100 {
+        let metrics = pool.metrics();
+        let polls = metrics.workers().iter().map(|w| w.polls()).sum::<usize>();
+        if polls == 3 && NUM_POLLS.load(Ordering::SeqCst) == 3 {
+            let busy = metrics.workers().iter().map(|w| w.busy_time()).max().unwrap();
+            assert!(busy >= Duration::from_millis(20));
+            return
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("polls weren't recorded");
+}
+
+#[test]
+fn metrics_queue_depth() {
+    let pool = CpuPool::new(1);
+    let (started_tx, started_rx) = std_mpsc::channel();
+    let (release_tx, release_rx) = std_mpsc::channel::<()>();
+    let blocker = pool.spawn_fn(move || {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        Ok::<(), ()>(())
+    });
+    started_rx.recv().unwrap();
+    let queued = (0..3).map(|i| pool.spawn(done(i))).collect::<Vec<_>>();
+    assert_eq!(pool.metrics().queue_depth(), 3);
+
+    release_tx.send(()).unwrap();
+    blocker.wait().unwrap();
+    futures::future::join_all(queued).wait().unwrap();
+    assert_eq!(pool.metrics().queue_depth(), 0);
+}
+
+#[test]
+fn scope_borrows_locals() {
+    let pool = CpuPool::new(4);
+    let data = (0..1000).collect::<Vec<u64>>();
+    let mut sums = [0; 10];
+    pool.scope(|s| {
+        for (chunk, sum) in data.chunks(100).zip(sums.iter_mut()) {
+            s.spawn(future::lazy(move || {
+                *sum = chunk.iter().sum();
+                Ok(())
+            }));
+        }
+    });
+    assert_eq!(sums.iter().sum::<u64>(), data.iter().sum::<u64>());
+}
+
+#[test]
+fn scope_waits_for_futures() {
+    let pool = CpuPool::new(2);
+    let finished = AtomicUsize::new(0);
+    pool.scope(|s| {
+        for _ in 0..4 {
+            let finished = &finished;
+            s.spawn(future::lazy(move || {
+                thread::sleep(Duration::from_millis(20));
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }));
+        }
+    });
+    assert_eq!(finished.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn scope_propagates_panics() {
+    let pool = CpuPool::new(2);
+    let finished = AtomicUsize::new(0);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.spawn(future::lazy(|| -> Result<(), ()> { panic!("boom") }));
+            let finished = &finished;
+            s.spawn(future::lazy(move || {
+                thread::sleep(Duration::from_millis(20));
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }));
+        })
+    }));
+    let err = res.unwrap_err();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"boom"));
+    // The other future still ran to completion first
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
+
+// Spawns `n` tasks which block until released, and waits until they're all
+// running at once.
+fn spawn_blocking(pool: &CpuPool, n: usize) -> (Vec<CpuFuture<(), ()>>, std_mpsc::Sender<()>) {
+    let running = Arc::new(AtomicUsize::new(0));
+    let (release_tx, release_rx) = std_mpsc::channel::<()>();
+    let release_rx = Arc::new(Mutex::new(release_rx));
+    let futures = (0..n).map(|_| {
+        let running = running.clone();
+        let release_rx = release_rx.clone();
+        pool.spawn_fn(move || {
+            running.fetch_add(1, Ordering::SeqCst);
+            let _ = release_rx.lock().unwrap().recv();
+            Ok(())
+        })
+    }).collect();
+    for _ in 0..100 {
+        if running.load(Ordering::SeqCst) == n {
+            return (futures, release_tx)
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("tasks didn't run concurrently");
+}
+
+#[test]
+fn grows_when_busy() {
+    let pool = Builder::new()
+        .min_threads(1)
+        .max_threads(4)
+        .create();
+    assert_eq!(pool.metrics().threads(), 1);
+    assert_eq!(pool.metrics().workers().len(), 1);
+
+    let (futures, release) = spawn_blocking(&pool, 4);
+    assert_eq!(pool.metrics().threads(), 4);
+    assert_eq!(pool.metrics().workers().len(), 4);
+    drop(release);
+    future::join_all(futures).wait().unwrap();
+}
+
+#[test]
+fn retires_idle_threads() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let stops = Arc::new(AtomicUsize::new(0));
+    let (starts2, stops2) = (starts.clone(), stops.clone());
+    let pool = Builder::new()
+        .min_threads(1)
+        .max_threads(3)
+        .keep_alive(Duration::from_millis(50))
+        .after_start(move || { starts2.fetch_add(1, Ordering::SeqCst); })
+        .before_stop(move || { stops2.fetch_add(1, Ordering::SeqCst); })
+        .create();
+
+    let (futures, release) = spawn_blocking(&pool, 3);
+    drop(release);
+    future::join_all(futures).wait().unwrap();
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+
+    for _ in 0..100 {
+        if stops.load(Ordering::SeqCst) == 2 {
+            assert_eq!(pool.metrics().threads(), 1);
+            // The pool still works, and grows again when needed
+            let (futures, release) = spawn_blocking(&pool, 2);
+            drop(release);
+            future::join_all(futures).wait().unwrap();
+            assert_eq!(starts.load(Ordering::SeqCst), 4);
+            // The new worker took over the slot of one that stopped
+            assert_eq!(pool.metrics().workers().len(), 3);
+            return
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("idle threads didn't stop");
+}
+
+#[test]
+fn no_min_threads() {
+    let pool = Builder::new()
+        .min_threads(0)
+        .max_threads(2)
+        .keep_alive(Duration::from_millis(20))
+        .create();
+    assert_eq!(pool.metrics().threads(), 0);
+    assert_eq!(pool.spawn(done(1)).wait(), Ok(1));
+
+    for _ in 0..100 {
+        if pool.metrics().threads() == 0 {
+            assert_eq!(pool.spawn(done(2)).wait(), Ok(2));
+            return
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("idle thread didn't stop");
+}
+
+#[test]
+fn unset_thread_bound_follows_the_other() {
+    let pool = Builder::new().max_threads(1).create();
+    assert_eq!(pool.metrics().threads(), 1);
+
+    let pool = Builder::new().min_threads(64).create();
+    assert_eq!(pool.metrics().threads(), 64);
+    let (futures, release) = spawn_blocking(&pool, 64);
+    drop(release);
+    future::join_all(futures).wait().unwrap();
+}
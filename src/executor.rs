@@ -8,3 +8,4 @@
 //! [online]: https://tokio.rs/docs/going-deeper/tasks/
 
 pub use task_impl::{Spawn, spawn, Unpark, Executor, Run};
+pub use task_impl::{LocalPool, LocalSpawner};
@@ -0,0 +1,226 @@
+use std::prelude::v1::*;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+
+use {Future, Poll, Async, Stream};
+use task::{self, Task};
+
+/// A future or stream which can be remotely aborted through an `AbortHandle`.
+///
+/// Once aborted, the next poll of this future or stream will fail with
+/// `AbortError::Aborted` without polling the underlying object again. This is
+/// created by the `future::abortable` and `stream::abortable` functions, or
+/// through `Abortable::new`.
+#[must_use = "futures do nothing unless polled"]
+pub struct Abortable<T> {
+    inner: T,
+    state: Arc<AbortState>,
+}
+
+/// A handle to an `Abortable` future or stream, used to abort it.
+///
+/// Handles can be cloned, and any clone may abort the associated future.
+#[derive(Clone)]
+pub struct AbortHandle {
+    state: Arc<AbortState>,
+}
+
+/// A registration handle for an `AbortHandle`, passed to `Abortable::new` to
+/// tie a future or stream to the handle.
+///
+/// This is created by `AbortHandle::new_pair`.
+pub struct AbortRegistration {
+    state: Arc<AbortState>,
+}
+
+struct AbortState {
+    aborted: AtomicBool,
+
+    // The task blocked on the abortable future, if any. The future stores its
+    // task here and then checks `aborted` again, while `abort` sets the flag
+    // before taking the task, so an abort can never be missed.
+    task: Mutex<Option<Task>>,
+}
+
+/// Error returned by an `Abortable` future or stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbortError<E> {
+    /// The future or stream was aborted through its `AbortHandle`.
+    Aborted,
+    /// The underlying future or stream produced an error.
+    Inner(E),
+}
+
+/// Wraps a future so that it can be aborted from elsewhere, returning the
+/// wrapped future along with the handle used to abort it.
+///
+/// When `AbortHandle::abort` is called the task polling the future is woken
+/// up, and the future will fail with `AbortError::Aborted`. The underlying
+/// future is dropped along with the `Abortable`.
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{self, AbortError, Future};
+///
+/// let (future, handle) = future::abortable(future::empty::<(), ()>());
+/// handle.abort();
+/// assert_eq!(future.wait(), Err(AbortError::Aborted));
+/// ```
+pub fn abortable<F>(future: F) -> (Abortable<F>, AbortHandle)
+    where F: Future,
+{
+    let (handle, reg) = AbortHandle::new_pair();
+    (Abortable::new(future, reg), handle)
+}
+
+impl<T> Abortable<T> {
+    /// Creates a new `Abortable` wrapping a future or stream, which will be
+    /// aborted by the `AbortHandle` that `reg` was created alongside.
+    pub fn new(inner: T, reg: AbortRegistration) -> Abortable<T> {
+        Abortable {
+            inner: inner,
+            state: reg.state,
+        }
+    }
+
+    /// Acquires a reference to the underlying future or stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Acquires a mutable reference to the underlying future or stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes this combinator, returning the underlying future or stream.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn poll_aborted(&self) -> bool {
+        if self.state.aborted.load(SeqCst) {
+            return true
+        }
+        // Store our task and then check again, see the comment on `task`
+        *self.state.task.lock().unwrap() = Some(task::park());
+        self.state.aborted.load(SeqCst)
+    }
+}
+
+impl<F> Future for Abortable<F>
+    where F: Future,
+{
+    type Item = F::Item;
+    type Error = AbortError<F::Error>;
+
+    fn poll(&mut self) -> Poll<F::Item, AbortError<F::Error>> {
+        if self.state.aborted.load(SeqCst) {
+            return Err(AbortError::Aborted)
+        }
+        match self.inner.poll() {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(e)) => return Ok(Async::Ready(e)),
+            Err(e) => return Err(AbortError::Inner(e)),
+        }
+        if self.poll_aborted() {
+            Err(AbortError::Aborted)
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl<S> Stream for Abortable<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = AbortError<S::Error>;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, AbortError<S::Error>> {
+        if self.state.aborted.load(SeqCst) {
+            return Err(AbortError::Aborted)
+        }
+        match self.inner.poll() {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(e)) => return Ok(Async::Ready(e)),
+            Err(e) => return Err(AbortError::Inner(e)),
+        }
+        if self.poll_aborted() {
+            Err(AbortError::Aborted)
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl AbortHandle {
+    /// Creates a new `AbortHandle` along with the `AbortRegistration` which
+    /// can be used to create an `Abortable` controlled by it.
+    ///
+    /// This is useful when the handle needs to exist before the future or
+    /// stream being aborted has been created.
+    pub fn new_pair() -> (AbortHandle, AbortRegistration) {
+        let state = Arc::new(AbortState {
+            aborted: AtomicBool::new(false),
+            task: Mutex::new(None),
+        });
+        (AbortHandle { state: state.clone() }, AbortRegistration { state: state })
+    }
+
+    /// Aborts the associated future or stream.
+    ///
+    /// The task currently blocked on it, if any, is woken up, and the next
+    /// poll will fail with `AbortError::Aborted`. Aborting a future which has
+    /// already completed has no effect.
+    pub fn abort(&self) {
+        self.state.aborted.store(true, SeqCst);
+        let task = self.state.task.lock().unwrap().take();
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+
+    /// Returns whether `abort` has been called on this handle or any of its
+    /// clones.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.load(SeqCst)
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AbortHandle")
+            .field("aborted", &self.is_aborted())
+            .finish()
+    }
+}
+
+impl fmt::Debug for AbortRegistration {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AbortRegistration").finish()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AbortError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AbortError::Aborted => write!(fmt, "future was aborted"),
+            AbortError::Inner(ref e) => e.fmt(fmt),
+        }
+    }
+}
+
+impl<E: Error> Error for AbortError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            AbortError::Aborted => "future was aborted",
+            AbortError::Inner(_) => "underlying future or stream failed",
+        }
+    }
+}
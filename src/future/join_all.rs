@@ -4,12 +4,99 @@
 use std::prelude::v1::*;
 
 use std::mem;
+use std::sync::Arc;
 
 use {Future, IntoFuture, Poll, Async};
+use stack::Stack;
+use task::{self, UnparkEvent};
 
 enum ElemState<T> where T: Future {
     Pending(T),
-    Done(T::Item),
+    Done(Result<T::Item, T::Error>),
+}
+
+// The list of futures shared by `JoinAll` and `JoinAllSettled`.
+//
+// Each future is polled with an `UnparkEvent` carrying its index, so when the
+// task is woken up only the futures which were notified are polled again
+// rather than the whole list.
+struct Elems<F> where F: Future {
+    elems: Vec<ElemState<F>>,
+    stack: Arc<Stack<usize>>,
+    remaining: usize,
+}
+
+impl<F> Elems<F> where F: Future {
+    fn new<I>(i: I) -> Elems<F>
+        where I: IntoIterator,
+              I::Item: IntoFuture<Future = F, Item = F::Item, Error = F::Error>,
+    {
+        let elems = i.into_iter().map(|f| {
+            ElemState::Pending(f.into_future())
+        }).collect::<Vec<_>>();
+
+        // Every future needs to be polled once to begin with. The stack is
+        // drained in LIFO order, so push the indices in reverse to poll the
+        // futures in the order they were given.
+        let stack = Arc::new(Stack::new());
+        for idx in (0..elems.len()).rev() {
+            stack.push(idx);
+        }
+        Elems {
+            remaining: elems.len(),
+            elems: elems,
+            stack: stack,
+        }
+    }
+
+    // Polls all futures which have been woken up, returning the first error
+    // encountered if `settle` is false. Otherwise errors are stored alongside
+    // the successful results.
+    fn poll(&mut self, settle: bool) -> Result<Async<()>, F::Error> {
+        for idx in self.stack.drain() {
+            let result = match self.elems[idx] {
+                ElemState::Pending(ref mut f) => {
+                    let event = UnparkEvent::new(self.stack.clone(), idx);
+                    match task::with_unpark_event(event, || f.poll()) {
+                        Ok(Async::NotReady) => continue,
+                        Ok(Async::Ready(v)) => Ok(v),
+                        Err(e) => Err(e),
+                    }
+                }
+                // Spurious notification for a future which is already done
+                ElemState::Done(_) => continue,
+            };
+            match result {
+                Err(e) if !settle => {
+                    // On completion drop all our associated resources
+                    // ASAP.
+                    self.elems = Vec::new();
+                    self.remaining = 0;
+                    return Err(e)
+                }
+                result => {
+                    self.elems[idx] = ElemState::Done(result);
+                    self.remaining -= 1;
+                }
+            }
+        }
+
+        if self.remaining == 0 {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+
+    fn take_results(&mut self) -> Vec<Result<F::Item, F::Error>> {
+        let elems = mem::replace(&mut self.elems, Vec::new());
+        elems.into_iter().map(|e| {
+            match e {
+                ElemState::Done(r) => r,
+                ElemState::Pending(_) => unreachable!(),
+            }
+        }).collect()
+    }
 }
 
 /// A future which takes a list of futures and resolves with a vector of the
@@ -21,7 +108,7 @@ pub struct JoinAll<I>
     where I: IntoIterator,
           I::Item: IntoFuture,
 {
-    elems: Vec<ElemState<<I::Item as IntoFuture>::Future>>,
+    elems: Elems<<I::Item as IntoFuture>::Future>,
 }
 
 /// Creates a future which represents a collection of the results of the futures
@@ -61,10 +148,7 @@ pub fn join_all<I>(i: I) -> JoinAll<I>
     where I: IntoIterator,
           I::Item: IntoFuture,
 {
-    let elems = i.into_iter().map(|f| {
-        ElemState::Pending(f.into_future())
-    }).collect();
-    JoinAll { elems: elems }
+    JoinAll { elems: Elems::new(i) }
 }
 
 impl<I> Future for JoinAll<I>
@@ -76,45 +160,69 @@ impl<I> Future for JoinAll<I>
 
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        let mut all_done = true;
+        try_ready!(self.elems.poll(false));
+        let result = self.elems.take_results().into_iter().map(|r| {
+            match r {
+                Ok(t) => t,
+                Err(_) => unreachable!(),
+            }
+        }).collect();
+        Ok(Async::Ready(result))
+    }
+}
 
-        for idx in 0 .. self.elems.len() {
-            let done_val = match &mut self.elems[idx] {
-                &mut ElemState::Pending(ref mut t) => {
-                    match t.poll() {
-                        Ok(Async::Ready(v)) => Ok(v),
-                        Ok(Async::NotReady) => {
-                            all_done = false;
-                            continue
-                        }
-                        Err(e) => Err(e),
-                    }
-                }
-                &mut ElemState::Done(ref mut _v) => continue,
-            };
+/// A future which takes a list of futures and resolves with a vector of all of
+/// their results, successful or not.
+///
+/// This future is created with the `join_all_settled` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct JoinAllSettled<I>
+    where I: IntoIterator,
+          I::Item: IntoFuture,
+{
+    elems: Elems<<I::Item as IntoFuture>::Future>,
+}
 
-            match done_val {
-                Ok(v) => self.elems[idx] = ElemState::Done(v),
-                Err(e) => {
-                    // On completion drop all our associated resources
-                    // ASAP.
-                    self.elems = Vec::new();
-                    return Err(e)
-                }
-            }
-        }
+/// Creates a future which waits for all of the futures given to complete,
+/// collecting their results.
+///
+/// Unlike `join_all`, an error from one of the futures does not cancel the
+/// others. Instead the returned future resolves to a `Vec` containing the
+/// `Result` of each future, in the same order as the futures were given. The
+/// returned future never fails.
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::*;
+///
+/// let f = join_all_settled(vec![
+///     ok::<u32, u32>(1),
+///     err::<u32, u32>(2),
+///     ok::<u32, u32>(3),
+/// ]);
+/// assert_eq!(f.wait(), Ok(vec![Ok(1), Err(2), Ok(3)]));
+/// ```
+pub fn join_all_settled<I>(i: I) -> JoinAllSettled<I>
+    where I: IntoIterator,
+          I::Item: IntoFuture,
+{
+    JoinAllSettled { elems: Elems::new(i) }
+}
 
-        if all_done {
-            let elems = mem::replace(&mut self.elems, Vec::new());
-            let result = elems.into_iter().map(|e| {
-                match e {
-                    ElemState::Done(t) => t,
-                    _ => unreachable!(),
-                }
-            }).collect();
-            Ok(Async::Ready(result))
-        } else {
-            Ok(Async::NotReady)
+impl<I> Future for JoinAllSettled<I>
+    where I: IntoIterator,
+          I::Item: IntoFuture,
+{
+    type Item = Vec<Result<<I::Item as IntoFuture>::Item,
+                           <I::Item as IntoFuture>::Error>>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, ()> {
+        match self.elems.poll(true) {
+            Ok(Async::Ready(())) => Ok(Async::Ready(self.elems.take_results())),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(_) => unreachable!(),
         }
     }
 }
@@ -42,6 +42,7 @@ mod and_then;
 mod flatten;
 mod flatten_stream;
 mod fuse;
+mod inspect;
 mod into_stream;
 mod join;
 mod map;
@@ -59,6 +60,7 @@ pub use self::and_then::AndThen;
 pub use self::flatten::Flatten;
 pub use self::flatten_stream::FlattenStream;
 pub use self::fuse::Fuse;
+pub use self::inspect::Inspect;
 pub use self::into_stream::IntoStream;
 pub use self::join::{Join, Join3, Join4, Join5};
 pub use self::map::Map;
@@ -70,16 +72,23 @@ pub use self::then::Then;
 pub use self::either::Either;
 
 if_std! {
+    mod abortable;
     mod catch_unwind;
     mod join_all;
+    mod retry;
     mod select_all;
     mod select_ok;
     mod shared;
+    mod timeout;
+    pub use self::abortable::{abortable, Abortable, AbortHandle, AbortRegistration, AbortError};
     pub use self::catch_unwind::CatchUnwind;
-    pub use self::join_all::{join_all, JoinAll};
+    pub use self::join_all::{join_all, JoinAll, join_all_settled, JoinAllSettled};
+    pub use self::retry::{retry, Retry, RetryPolicy, FixedBackoff, ExponentialBackoff};
+    pub use self::retry::{MaxAttempts, RetryIf};
     pub use self::select_all::{SelectAll, SelectAllNext, select_all};
     pub use self::select_ok::{SelectOk, select_ok};
     pub use self::shared::{Shared, SharedItem, SharedError};
+    pub use self::timeout::Timeout;
 
     #[doc(hidden)]
     #[deprecated(since = "0.1.4", note = "use join_all instead")]
@@ -358,6 +367,30 @@ pub trait Future {
         assert_future::<Self::Item, E, _>(map_err::new(self, f))
     }
 
+    /// Do something with the item of a future, passing it on.
+    ///
+    /// The closure provided is called with a reference to the value this
+    /// future resolves to, just before it's returned. This is useful for
+    /// observing a value in the middle of a chain of combinators, for example
+    /// to log it, without changing its type. Errors are passed through without
+    /// calling the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::future::*;
+    ///
+    /// let future = ok::<u32, u32>(1);
+    /// let new_future = future.inspect(|&x| println!("about to resolve: {}", x));
+    /// assert_eq!(new_future.wait(), Ok(1));
+    /// ```
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+        where F: FnOnce(&Self::Item),
+              Self: Sized,
+    {
+        assert_future::<Self::Item, Self::Error, _>(inspect::new(self, f))
+    }
+
 
 
     /// Map this future's error to any error implementing `From` for
@@ -834,6 +867,33 @@ pub trait Future {
     {
         Shared::new(self)
     }
+
+    /// Fails this future with `TimeoutError::Elapsed` if it doesn't complete
+    /// within `dur`.
+    ///
+    /// The deadline is tracked by the default timer (see `Timer::default`),
+    /// and starts counting when this method is called. Errors from the
+    /// underlying future are passed through as `TimeoutError::Inner`.
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use futures::future::*;
+    /// use futures::timer::TimeoutError;
+    ///
+    /// let future = empty::<(), ()>().timeout(Duration::from_millis(10));
+    /// assert_eq!(future.wait(), Err(TimeoutError::Elapsed));
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn timeout(self, dur: ::std::time::Duration) -> Timeout<Self>
+        where Self: Sized
+    {
+        timeout::new(self, dur)
+    }
 }
 
 impl<'a, F: ?Sized + Future> Future for &'a mut F {
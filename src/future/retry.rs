@@ -0,0 +1,338 @@
+use std::prelude::v1::*;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT};
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use {Future, IntoFuture, Poll, Async};
+use timer::{Delay, Timer};
+
+/// A policy deciding whether, and after how long, a failed operation is
+/// retried by `future::retry`.
+///
+/// This library ships with `FixedBackoff` and `ExponentialBackoff` schedules,
+/// which retry every error forever, along with the `max_attempts` and
+/// `retry_if` adaptors to limit them. Policies can be stateful, as a new
+/// policy is used for each call to `retry`.
+pub trait RetryPolicy<E> {
+    /// Called when attempt number `attempt` (starting at 1) fails with
+    /// `error`.
+    ///
+    /// Returns how long to wait before the next attempt, or `None` if the
+    /// error should be returned instead of retrying.
+    fn retry(&mut self, attempt: usize, error: &E) -> Option<Duration>;
+
+    /// Limits this policy to at most `attempts` attempts in total, after which
+    /// the last error is returned.
+    fn max_attempts(self, attempts: usize) -> MaxAttempts<Self, E>
+        where Self: Sized
+    {
+        MaxAttempts {
+            policy: self,
+            attempts: attempts,
+            _error: PhantomData,
+        }
+    }
+
+    /// Only retries errors for which `f` returns `true`, returning any other
+    /// error straight away.
+    fn retry_if<F>(self, f: F) -> RetryIf<Self, F>
+        where F: FnMut(&E) -> bool,
+              Self: Sized
+    {
+        RetryIf {
+            policy: self,
+            f: f,
+        }
+    }
+}
+
+/// Retries an operation until it succeeds or `policy` gives up.
+///
+/// The closure `make` is called to create the future for each attempt. When
+/// that future fails, `policy` is consulted with the error, and the future
+/// either sleeps for the returned delay before calling `make` again or fails
+/// with the error.
+///
+/// The delays are tracked by the default timer (see `Timer::default`) at the
+/// time this function is called.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use futures::future::{self, Future, FixedBackoff, RetryPolicy};
+///
+/// let mut attempts = 0;
+/// let policy = FixedBackoff::new(Duration::from_millis(1)).max_attempts(5);
+/// let future = future::retry(policy, || {
+///     attempts += 1;
+///     if attempts < 3 { Err(attempts) } else { Ok(attempts) }
+/// });
+/// assert_eq!(future.wait(), Ok(3));
+/// ```
+pub fn retry<P, F, R>(policy: P, make: F) -> Retry<P, F, R>
+    where F: FnMut() -> R,
+          R: IntoFuture,
+          P: RetryPolicy<R::Error>,
+{
+    Retry {
+        policy: policy,
+        make: make,
+        timer: Timer::default(),
+        attempt: 0,
+        state: State::Idle,
+    }
+}
+
+/// Future for the `retry` function, re-running an operation on failure.
+#[must_use = "futures do nothing unless polled"]
+pub struct Retry<P, F, R>
+    where R: IntoFuture,
+{
+    policy: P,
+    make: F,
+    timer: Timer,
+    // Number of attempts which have been started so far
+    attempt: usize,
+    state: State<R::Future>,
+}
+
+enum State<F> {
+    Idle,
+    Running(F),
+    Sleeping(Delay),
+}
+
+impl<P, F, R> Future for Retry<P, F, R>
+    where F: FnMut() -> R,
+          R: IntoFuture,
+          P: RetryPolicy<R::Error>,
+{
+    type Item = R::Item;
+    type Error = R::Error;
+
+    fn poll(&mut self) -> Poll<R::Item, R::Error> {
+        loop {
+            let error = match self.state {
+                State::Idle => {
+                    self.attempt += 1;
+                    self.state = State::Running((self.make)().into_future());
+                    continue
+                }
+                State::Running(ref mut f) => {
+                    match f.poll() {
+                        Ok(a) => return Ok(a),
+                        Err(e) => e,
+                    }
+                }
+                State::Sleeping(ref mut delay) => {
+                    match delay.poll() {
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Ok(Async::Ready(())) | Err(()) => {}
+                    }
+                    self.state = State::Idle;
+                    continue
+                }
+            };
+
+            match self.policy.retry(self.attempt, &error) {
+                Some(dur) => {
+                    let at = self.timer.now() + dur;
+                    self.state = State::Sleeping(self.timer.delay_until(at));
+                }
+                None => {
+                    self.state = State::Idle;
+                    return Err(error)
+                }
+            }
+        }
+    }
+}
+
+/// A retry policy which waits for the same amount of time between each
+/// attempt.
+#[derive(Debug, Clone)]
+pub struct FixedBackoff {
+    delay: Duration,
+}
+
+impl FixedBackoff {
+    /// Creates a new policy waiting for `delay` between attempts.
+    pub fn new(delay: Duration) -> FixedBackoff {
+        FixedBackoff { delay: delay }
+    }
+}
+
+impl<E> RetryPolicy<E> for FixedBackoff {
+    fn retry(&mut self, _attempt: usize, _error: &E) -> Option<Duration> {
+        Some(self.delay)
+    }
+}
+
+/// A retry policy which multiplies the time waited between attempts after
+/// each failure.
+///
+/// By default the delay doubles after each attempt, with no upper bound.
+/// Jitter can be enabled with `jitter`, in which case each delay is instead
+/// chosen uniformly at random between zero and the exponential delay, which
+/// prevents many clients that failed together from retrying in lockstep.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    next: Duration,
+    factor: u32,
+    max_delay: Option<Duration>,
+    jitter: bool,
+}
+
+impl ExponentialBackoff {
+    /// Creates a new policy waiting for `initial` after the first attempt.
+    pub fn new(initial: Duration) -> ExponentialBackoff {
+        ExponentialBackoff {
+            next: initial,
+            factor: 2,
+            max_delay: None,
+            jitter: false,
+        }
+    }
+
+    /// Sets the factor the delay is multiplied by after each attempt.
+    pub fn factor(mut self, factor: u32) -> ExponentialBackoff {
+        self.factor = factor;
+        self
+    }
+
+    /// Caps the delay between attempts to at most `max`.
+    pub fn max_delay(mut self, max: Duration) -> ExponentialBackoff {
+        self.max_delay = Some(max);
+        self
+    }
+
+    /// Enables full jitter, randomizing each delay between zero and the
+    /// exponential delay.
+    pub fn jitter(mut self) -> ExponentialBackoff {
+        self.jitter = true;
+        self
+    }
+}
+
+impl<E> RetryPolicy<E> for ExponentialBackoff {
+    fn retry(&mut self, _attempt: usize, _error: &E) -> Option<Duration> {
+        let mut delay = self.next;
+        if let Some(max) = self.max_delay {
+            if delay > max {
+                delay = max;
+            }
+        }
+        self.next = match checked_mul(self.next, self.factor) {
+            Some(next) => next,
+            None => self.max_delay.unwrap_or(self.next),
+        };
+        if self.jitter {
+            delay = jitter(delay);
+        }
+        Some(delay)
+    }
+}
+
+// Multiplies `dur` by `factor`, returning `None` on overflow. This is done in
+// nanoseconds by hand as `Duration::checked_mul` isn't available on all the
+// versions of Rust supported.
+fn checked_mul(dur: Duration, factor: u32) -> Option<Duration> {
+    dur.as_secs().checked_mul(1_000_000_000)
+        .and_then(|nanos| nanos.checked_add(dur.subsec_nanos() as u64))
+        .and_then(|nanos| nanos.checked_mul(factor as u64))
+        .map(|nanos| Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32))
+}
+
+// Returns a random duration between zero and `max`.
+//
+// This only needs to spread out retries rather than be unpredictable, so a
+// xorshift generator seeded from the system clock and a counter is plenty.
+fn jitter(max: Duration) -> Duration {
+    static COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let mut x = nanos ^ ((COUNTER.fetch_add(1, Relaxed) as u64) << 32) ^ 0x9e3779b97f4a7c15;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+
+    let max_nanos = max.as_secs()
+        .saturating_mul(1_000_000_000)
+        .saturating_add(max.subsec_nanos() as u64);
+    if max_nanos == 0 {
+        return max
+    }
+    let nanos = x % (max_nanos + 1);
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
+
+/// A retry policy which gives up after a maximum number of attempts.
+///
+/// This is created by the `RetryPolicy::max_attempts` method.
+pub struct MaxAttempts<P, E> {
+    policy: P,
+    attempts: usize,
+    // Ties this adaptor to one error type so it can be inferred
+    _error: PhantomData<fn(&E)>,
+}
+
+// These are implemented by hand as deriving them would also require `E` to
+// implement them, through the marker.
+impl<P: Clone, E> Clone for MaxAttempts<P, E> {
+    fn clone(&self) -> MaxAttempts<P, E> {
+        MaxAttempts {
+            policy: self.policy.clone(),
+            attempts: self.attempts,
+            _error: PhantomData,
+        }
+    }
+}
+
+impl<P: fmt::Debug, E> fmt::Debug for MaxAttempts<P, E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("MaxAttempts")
+            .field("policy", &self.policy)
+            .field("attempts", &self.attempts)
+            .finish()
+    }
+}
+
+impl<P, E> RetryPolicy<E> for MaxAttempts<P, E>
+    where P: RetryPolicy<E>,
+{
+    fn retry(&mut self, attempt: usize, error: &E) -> Option<Duration> {
+        if attempt >= self.attempts {
+            None
+        } else {
+            self.policy.retry(attempt, error)
+        }
+    }
+}
+
+/// A retry policy which only retries some errors.
+///
+/// This is created by the `RetryPolicy::retry_if` method.
+#[derive(Debug, Clone)]
+pub struct RetryIf<P, F> {
+    policy: P,
+    f: F,
+}
+
+impl<P, F, E> RetryPolicy<E> for RetryIf<P, F>
+    where P: RetryPolicy<E>,
+          F: FnMut(&E) -> bool,
+{
+    fn retry(&mut self, attempt: usize, error: &E) -> Option<Duration> {
+        if (self.f)(error) {
+            self.policy.retry(attempt, error)
+        } else {
+            None
+        }
+    }
+}
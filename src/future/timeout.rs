@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use {Future, Poll, Async};
+use timer::{Delay, Timer, TimeoutError};
+
+/// Future for the `timeout` combinator, failing with `TimeoutError::Elapsed`
+/// if the underlying future doesn't complete in time.
+///
+/// This is created by the `Future::timeout` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F> {
+    future: F,
+    delay: Delay,
+}
+
+pub fn new<F>(future: F, dur: Duration) -> Timeout<F>
+    where F: Future,
+{
+    Timeout {
+        future: future,
+        delay: Timer::default().delay(dur),
+    }
+}
+
+impl<F> Timeout<F> {
+    /// Acquires a reference to the underlying future.
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Acquires a mutable reference to the underlying future.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.future
+    }
+
+    /// Consumes this combinator, returning the underlying future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F> Future for Timeout<F>
+    where F: Future,
+{
+    type Item = F::Item;
+    type Error = TimeoutError<F::Error>;
+
+    fn poll(&mut self) -> Poll<F::Item, TimeoutError<F::Error>> {
+        match self.future.poll() {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(e)) => return Ok(Async::Ready(e)),
+            Err(e) => return Err(TimeoutError::Inner(e)),
+        }
+        match self.delay.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(())) | Err(()) => Err(TimeoutError::Elapsed),
+        }
+    }
+}
@@ -203,10 +203,12 @@ if_std! {
     mod lock;
     mod task_impl;
     mod stack;
+    mod token_bucket;
 
     pub mod task;
     pub mod executor;
     pub mod sync;
+    pub mod timer;
 
     #[doc(hidden)]
     #[deprecated(since = "0.1.4", note = "use sync::oneshot::channel instead")]
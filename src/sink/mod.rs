@@ -21,8 +21,10 @@ mod send_all;
 
 if_std! {
     mod buffer;
+    mod rate_limit;
 
     pub use self::buffer::Buffer;
+    pub use self::rate_limit::RateLimit;
 
     // TODO: consider expanding this via e.g. FromIterator
     impl<T> Sink for ::std::vec::Vec<T> {
@@ -233,6 +235,29 @@ pub trait Sink {
         buffer::new(self, amt)
     }
 
+    /// Limits the rate at which items are sent to this sink to `n` items per
+    /// `per`.
+    ///
+    /// The limit is implemented as a token bucket: up to `n` items may be sent
+    /// back to back (see `RateLimit::burst` to change this), after which
+    /// `start_send` will return `AsyncSink::NotReady` until the next item is
+    /// allowed through. This applies backpressure to the code feeding the
+    /// sink, such as `Stream::forward`, without blocking the thread. The time
+    /// is tracked by the default timer (see `Timer::default`).
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `n` is zero.
+    #[cfg(feature = "use_std")]
+    fn rate_limit(self, n: usize, per: ::std::time::Duration) -> RateLimit<Self>
+        where Self: Sized
+    {
+        rate_limit::new(self, n, per)
+    }
+
     /// A future that completes when the sink has finished processing all
     /// pending requests.
     ///
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use {Async, AsyncSink, Poll, StartSend};
+use sink::Sink;
+use stream::Stream;
+use token_bucket::TokenBucket;
+
+/// Sink for the `Sink::rate_limit` combinator, which limits the rate at which
+/// items are passed on to the underlying sink.
+///
+/// Once the limit has been reached `start_send` returns `AsyncSink::NotReady`
+/// until the next item is allowed through, applying backpressure to whatever
+/// is feeding the sink.
+#[must_use = "sinks do nothing unless polled"]
+pub struct RateLimit<S> {
+    sink: S,
+    bucket: TokenBucket,
+}
+
+pub fn new<S: Sink>(sink: S, n: usize, per: Duration) -> RateLimit<S> {
+    RateLimit {
+        sink: sink,
+        bucket: TokenBucket::new(n, per),
+    }
+}
+
+impl<S> RateLimit<S> {
+    /// Sets the number of items which may be sent back to back after the sink
+    /// has been idle, which defaults to the `n` given to `rate_limit`.
+    ///
+    /// The average rate stays the same regardless of the burst size.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `burst` is zero.
+    pub fn burst(mut self, burst: usize) -> RateLimit<S> {
+        self.bucket.set_burst(burst);
+        self
+    }
+
+    /// Get a shared reference to the inner sink.
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the inner sink.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Consumes this combinator, returning the underlying sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+// Forwarding impl of Stream from the underlying sink
+impl<S> Stream for RateLimit<S> where S: Stream {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        self.sink.poll()
+    }
+}
+
+impl<S: Sink> Sink for RateLimit<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> StartSend<S::SinkItem, S::SinkError> {
+        if let Async::NotReady = self.bucket.poll_ready() {
+            return Ok(AsyncSink::NotReady(item))
+        }
+        let ret = try!(self.sink.start_send(item));
+        if let AsyncSink::Ready = ret {
+            self.bucket.take();
+        }
+        Ok(ret)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        self.sink.poll_complete()
+    }
+}
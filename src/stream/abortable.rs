@@ -0,0 +1,27 @@
+use stream::Stream;
+use future::{Abortable, AbortHandle};
+
+/// Wraps a stream so that it can be aborted from elsewhere, returning the
+/// wrapped stream along with the handle used to abort it.
+///
+/// When `AbortHandle::abort` is called the task polling the stream is woken
+/// up, and the stream will fail with `AbortError::Aborted` instead of yielding
+/// any further items.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{Async, Stream};
+/// use futures::stream::{self, AbortError};
+///
+/// let (mut stream, handle) = stream::abortable(stream::iter(vec![Ok::<u32, ()>(1), Ok(2)]));
+/// assert_eq!(stream.poll(), Ok(Async::Ready(Some(1))));
+/// handle.abort();
+/// assert_eq!(stream.poll(), Err(AbortError::Aborted));
+/// ```
+pub fn abortable<S>(stream: S) -> (Abortable<S>, AbortHandle)
+    where S: Stream,
+{
+    let (handle, reg) = AbortHandle::new_pair();
+    (Abortable::new(stream, reg), handle)
+}
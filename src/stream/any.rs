@@ -0,0 +1,49 @@
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which tests whether any item of a stream matches a predicate.
+///
+/// This future is created by the `Stream::any` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Any<S, P> {
+    stream: Option<S>,
+    pred: P,
+}
+
+pub fn new<S, P>(s: S, pred: P) -> Any<S, P>
+    where S: Stream,
+          P: FnMut(S::Item) -> bool,
+{
+    Any {
+        stream: Some(s),
+        pred: pred,
+    }
+}
+
+impl<S, P> Future for Any<S, P>
+    where S: Stream,
+          P: FnMut(S::Item) -> bool,
+{
+    type Item = bool;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<bool, S::Error> {
+        loop {
+            let res = self.stream.as_mut().expect("cannot poll Any twice").poll();
+            let ret = match res {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(Some(e))) => {
+                    if !(self.pred)(e) {
+                        continue
+                    }
+                    Ok(true)
+                }
+                Ok(Async::Ready(None)) => Ok(false),
+                Err(e) => Err(e),
+            };
+            // Drop the stream as soon as the answer is known
+            self.stream = None;
+            return ret.map(Async::Ready)
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::mem;
+use std::prelude::v1::*;
+use std::time::Duration;
+
+use {Async, Future, Poll};
+use stream::{Stream, Fuse};
+use timer::{Delay, Timer};
+
+/// An adaptor that chunks up elements in a vector, flushing partial chunks
+/// after a deadline.
+///
+/// This is like `Chunks`, except that a chunk is also passed on once `dur`
+/// has elapsed since its first item was buffered, even if it isn't full yet.
+/// This is created by the `Stream::chunks_timeout` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct ChunksTimeout<S>
+    where S: Stream
+{
+    items: Vec<S::Item>,
+    err: Option<S::Error>,
+    stream: Fuse<S>,
+    timer: Timer,
+    dur: Duration,
+    // The deadline for the current chunk, set while it's non-empty
+    delay: Option<Delay>,
+}
+
+pub fn new<S>(s: S, capacity: usize, dur: Duration) -> ChunksTimeout<S>
+    where S: Stream
+{
+    assert!(capacity > 0);
+
+    ChunksTimeout {
+        items: Vec::with_capacity(capacity),
+        err: None,
+        stream: super::fuse::new(s),
+        timer: Timer::default(),
+        dur: dur,
+        delay: None,
+    }
+}
+
+impl<S> ChunksTimeout<S> where S: Stream {
+    fn take(&mut self) -> Vec<S::Item> {
+        let cap = self.items.capacity();
+        self.delay = None;
+        mem::replace(&mut self.items, Vec::with_capacity(cap))
+    }
+}
+
+impl<S> Stream for ChunksTimeout<S>
+    where S: Stream
+{
+    type Item = Vec<<S as Stream>::Item>;
+    type Error = <S as Stream>::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(err) = self.err.take() {
+            return Err(err)
+        }
+
+        let cap = self.items.capacity();
+        loop {
+            match self.stream.poll() {
+                Ok(Async::NotReady) => break,
+
+                // Push the item into the buffer, starting the deadline if it's
+                // the first one, and check whether the buffer is full.
+                Ok(Async::Ready(Some(item))) => {
+                    if self.items.is_empty() {
+                        self.delay = Some(self.timer.delay(self.dur));
+                    }
+                    self.items.push(item);
+                    if self.items.len() >= cap {
+                        return Ok(Some(self.take()).into())
+                    }
+                }
+
+                // Since the underlying stream ran out of values, return what we
+                // have buffered, if we have anything.
+                Ok(Async::Ready(None)) => {
+                    return if self.items.is_empty() {
+                        Ok(Async::Ready(None))
+                    } else {
+                        Ok(Some(self.take()).into())
+                    }
+                }
+
+                // If we've got buffered items be sure to return them first,
+                // we'll defer our error for later.
+                Err(e) => {
+                    if self.items.is_empty() {
+                        return Err(e)
+                    } else {
+                        self.err = Some(e);
+                        return Ok(Some(self.take()).into())
+                    }
+                }
+            }
+        }
+
+        // The stream isn't ready, so flush a partial chunk if its deadline
+        // has passed.
+        let expired = match self.delay {
+            Some(ref mut delay) => match delay.poll() {
+                Ok(Async::NotReady) => false,
+                Ok(Async::Ready(())) | Err(()) => true,
+            },
+            None => false,
+        };
+        if expired {
+            Ok(Some(self.take()).into())
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which concatenates all items of a stream into the first one.
+///
+/// This future is created by the `Stream::concat` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Concat<S> where S: Stream {
+    stream: S,
+    acc: Option<S::Item>,
+}
+
+pub fn new<S>(s: S) -> Concat<S>
+    where S: Stream,
+          S::Item: Extend<<S::Item as IntoIterator>::Item> + IntoIterator + Default,
+{
+    Concat {
+        stream: s,
+        acc: None,
+    }
+}
+
+impl<S> Future for Concat<S>
+    where S: Stream,
+          S::Item: Extend<<S::Item as IntoIterator>::Item> + IntoIterator + Default,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<S::Item, S::Error> {
+        loop {
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(e))) => {
+                    // The first item becomes the accumulator, so for example
+                    // the first `Vec` is reused rather than copied.
+                    match self.acc {
+                        Some(ref mut acc) => acc.extend(e),
+                        None => self.acc = Some(e),
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    return Ok(Async::Ready(self.acc.take().unwrap_or_else(Default::default)))
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.acc = None;
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which counts the number of items in a stream.
+///
+/// This future is created by the `Stream::count` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct Count<S> {
+    stream: S,
+    count: usize,
+}
+
+pub fn new<S>(s: S) -> Count<S>
+    where S: Stream,
+{
+    Count {
+        stream: s,
+        count: 0,
+    }
+}
+
+impl<S> Future for Count<S>
+    where S: Stream,
+{
+    type Item = usize;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<usize, S::Error> {
+        while try_ready!(self.stream.poll()).is_some() {
+            self.count += 1;
+        }
+        Ok(Async::Ready(self.count))
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use {Async, Future, Poll};
+use stream::{Stream, Fuse};
+use timer::{Delay, Timer};
+
+/// A stream combinator which only yields an item once a period has passed
+/// without the underlying stream producing another one.
+///
+/// This is created by the `Stream::debounce` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Debounce<S> where S: Stream {
+    stream: Fuse<S>,
+    dur: Duration,
+    delay: Delay,
+    // The latest item, waiting for the stream to go quiet
+    pending: Option<S::Item>,
+}
+
+pub fn new<S>(stream: S, dur: Duration) -> Debounce<S>
+    where S: Stream,
+{
+    Debounce {
+        stream: super::fuse::new(stream),
+        dur: dur,
+        delay: Timer::default().delay(dur),
+        pending: None,
+    }
+}
+
+impl<S> Stream for Debounce<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                // Each item replaces the pending one and restarts the wait
+                Async::Ready(Some(item)) => {
+                    self.pending = Some(item);
+                    let next = self.delay.timer().now() + self.dur;
+                    self.delay.reset(next);
+                }
+                // The stream is done, so there's nothing left to wait for
+                Async::Ready(None) => return Ok(Async::Ready(self.pending.take())),
+                Async::NotReady => break,
+            }
+        }
+
+        if self.pending.is_none() {
+            return Ok(Async::NotReady)
+        }
+        match self.delay.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(())) | Err(()) => Ok(Async::Ready(self.pending.take())),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use {Async, Poll};
+use stream::Stream;
+
+/// A stream combinator which pairs each item with its index in the stream.
+///
+/// This is produced by the `Stream::enumerate` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Enumerate<S> {
+    stream: S,
+    count: usize,
+}
+
+pub fn new<S>(s: S) -> Enumerate<S>
+    where S: Stream,
+{
+    Enumerate {
+        stream: s,
+        count: 0,
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+impl<S> ::sink::Sink for Enumerate<S>
+    where S: ::sink::Sink
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> ::StartSend<S::SinkItem, S::SinkError> {
+        self.stream.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        self.stream.poll_complete()
+    }
+}
+
+impl<S> Stream for Enumerate<S>
+    where S: Stream,
+{
+    type Item = (usize, S::Item);
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<(usize, S::Item)>, S::Error> {
+        match try_ready!(self.stream.poll()) {
+            Some(e) => {
+                let count = self.count;
+                self.count += 1;
+                Ok(Async::Ready(Some((count, e))))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use {Async, Future, IntoFuture, Poll};
+use stream::{Stream, FuturesUnordered};
+
+/// A future which executes a unit closure over each item on a stream, running
+/// up to a fixed number of the resulting futures at once.
+///
+/// This structure is returned by the `Stream::for_each_concurrent` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct ForEachConcurrent<S, F, U> where U: IntoFuture {
+    // `None` once the stream has finished or an error has happened
+    stream: Option<S>,
+    f: F,
+    futures: FuturesUnordered<U::Future>,
+    limit: usize,
+}
+
+pub fn new<S, F, U>(s: S, limit: usize, f: F) -> ForEachConcurrent<S, F, U>
+    where S: Stream,
+          F: FnMut(S::Item) -> U,
+          U: IntoFuture<Item = (), Error = S::Error>,
+{
+    assert!(limit > 0, "limit cannot be zero");
+    ForEachConcurrent {
+        stream: Some(s),
+        f: f,
+        futures: FuturesUnordered::new(),
+        limit: limit,
+    }
+}
+
+impl<S, F, U> ForEachConcurrent<S, F, U>
+    where U: IntoFuture,
+{
+    // Drops the stream and all in-flight futures after an error
+    fn cancel(&mut self) {
+        self.stream = None;
+        self.futures = FuturesUnordered::new();
+    }
+}
+
+impl<S, F, U> Future for ForEachConcurrent<S, F, U>
+    where S: Stream,
+          F: FnMut(S::Item) -> U,
+          U: IntoFuture<Item = (), Error = S::Error>,
+{
+    type Item = ();
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<(), S::Error> {
+        loop {
+            // First up, try to spawn off as many futures as possible by filling
+            // up our set of in-flight futures.
+            while self.futures.len() < self.limit {
+                let item = match self.stream {
+                    Some(ref mut s) => s.poll(),
+                    None => break,
+                };
+                match item {
+                    Ok(Async::Ready(Some(e))) => {
+                        self.futures.push((self.f)(e).into_future());
+                    }
+                    Ok(Async::Ready(None)) => self.stream = None,
+                    Ok(Async::NotReady) => break,
+                    Err(e) => {
+                        self.cancel();
+                        return Err(e)
+                    }
+                }
+            }
+
+            // Then drive the futures, going back to pull more items off the
+            // stream whenever one of them finishes.
+            match self.futures.poll() {
+                Ok(Async::Ready(_)) => {}
+                Ok(Async::NotReady) => {
+                    if self.stream.is_none() && self.futures.is_empty() {
+                        return Ok(Async::Ready(()))
+                    }
+                    return Ok(Async::NotReady)
+                }
+                Err(e) => {
+                    self.cancel();
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
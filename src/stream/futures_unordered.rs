@@ -4,26 +4,50 @@ use poll::Poll;
 use Async;
 use stack::{Stack, Drain};
 use std::sync::Arc;
+use std::mem;
 use task::{self, UnparkEvent};
 
 use std::prelude::v1::*;
 
-/// An adaptor for a stream of futures to execute the futures concurrently, if
+/// An adaptor for a set of futures to execute the futures concurrently, if
 /// possible, delivering results as they become available.
 ///
 /// This adaptor will return their results in the order that they complete.
-/// This is created by the `futures` method.
+/// This is created by the `futures_unordered` function or by
+/// `FuturesUnordered::new`, and more futures can be added to a running set
+/// with the `push` method.
 ///
+/// Note that an empty set is not considered finished: polling it will return
+/// `NotReady` as more futures may be pushed later. Use `is_empty` to find out
+/// whether any futures are still running.
 #[must_use = "streams do nothing unless polled"]
 pub struct FuturesUnordered<F>
     where F: Future
 {
-    futures: Vec<Option<F>>,
+    // A slab of futures that are being executed. Each slot in this vector is
+    // either an active future or a pointer to the next empty slot, which is
+    // how slots of completed futures are reused by `push`.
+    //
+    // The `next_future` field is the next slot in the `futures` array that's a
+    // `Slot::Next` variant. If it points to the end of the array then the array
+    // is full and it'll be grown on the next push.
+    futures: Vec<Slot<F>>,
+    next_future: usize,
+
+    // Events pushed onto concurrently by our futures through
+    // `with_unpark_event`, and the last batch of events we drained from it.
     stack: Arc<Stack<usize>>,
-    pending: Option<Drain<usize>>,
+    pending: Drain<usize>,
+
+    // Number of active futures running in the `futures` slab
     active: usize,
 }
 
+enum Slot<T> {
+    Next(usize),
+    Data(T),
+}
+
 /// Converts a list of futures into a `Stream` of results from the futures.
 ///
 /// This function will take an list of futures (e.g. a vector, an iterator,
@@ -35,47 +59,82 @@ pub fn futures_unordered<I>(futures: I) -> FuturesUnordered<<I::Item as IntoFutu
     where I: IntoIterator,
           I::Item: IntoFuture
 {
-    let futures = futures.into_iter()
-                         .map(IntoFuture::into_future)
-                         .map(Some)
-                         .collect::<Vec<_>>();
-    let stack = Arc::new(Stack::new());
-    for i in 0..futures.len() {
-        stack.push(i);
-    }
-    FuturesUnordered {
-        active: futures.len(),
-        futures: futures,
-        pending: None,
-        stack: stack,
+    let mut set = FuturesUnordered::new();
+    for future in futures {
+        set.push(future.into_future());
     }
+    set
 }
 
 impl<F> FuturesUnordered<F>
     where F: Future
 {
-    fn poll_pending(&mut self, mut drain: Drain<usize>)
-                    -> Option<Poll<Option<F::Item>, F::Error>> {
-        while let Some(id) = drain.next() {
-            // If this future was already done just skip the notification
-            if self.futures[id].is_none() {
-                continue
-            }
-            let event = UnparkEvent::new(self.stack.clone(), id);
-            let ret = match task::with_unpark_event(event, || {
-                self.futures[id]
-                    .as_mut()
-                    .unwrap()
-                    .poll()
-            }) {
-                Ok(Async::NotReady) => continue,
-                Ok(Async::Ready(val)) => Ok(Async::Ready(Some(val))),
-                Err(e) => Err(e),
+    /// Constructs a new, empty `FuturesUnordered`.
+    ///
+    /// The returned set does not contain any futures, and in this state
+    /// polling it will return `NotReady`. Futures can be added with `push`.
+    pub fn new() -> FuturesUnordered<F> {
+        FuturesUnordered {
+            futures: Vec::new(),
+            next_future: 0,
+            stack: Arc::new(Stack::new()),
+            pending: Stack::new().drain(),
+            active: 0,
+        }
+    }
+
+    /// Returns the number of futures contained in the set.
+    ///
+    /// This represents the total number of in-flight futures.
+    pub fn len(&self) -> usize {
+        self.active
+    }
+
+    /// Returns `true` if the set contains no futures.
+    pub fn is_empty(&self) -> bool {
+        self.active == 0
+    }
+
+    /// Push a future into the set.
+    ///
+    /// This function submits the given future to the set for managing. This
+    /// function will not call `poll` on the submitted future. The caller must
+    /// ensure that `FuturesUnordered::poll` is called in order to receive
+    /// notifications and its result.
+    ///
+    /// Slots freed by previously completed futures are reused, so a
+    /// long-running set only grows to its peak number of in-flight futures.
+    pub fn push(&mut self, future: F) {
+        let idx = self.next_future;
+        if idx == self.futures.len() {
+            self.futures.push(Slot::Next(idx + 1));
+        }
+        match mem::replace(&mut self.futures[idx], Slot::Data(future)) {
+            Slot::Next(next) => self.next_future = next,
+            Slot::Data(_) => panic!(),
+        }
+        self.active += 1;
+        self.stack.push(idx);
+    }
+
+    fn poll_pending(&mut self) -> Option<Poll<Option<F::Item>, F::Error>> {
+        while let Some(idx) = self.pending.next() {
+            let result = match self.futures[idx] {
+                Slot::Data(ref mut f) => {
+                    let event = UnparkEvent::new(self.stack.clone(), idx);
+                    match task::with_unpark_event(event, || f.poll()) {
+                        Ok(Async::NotReady) => continue,
+                        Ok(Async::Ready(e)) => Ok(Async::Ready(Some(e))),
+                        Err(e) => Err(e),
+                    }
+                }
+                // If this future was already done just skip the notification
+                Slot::Next(_) => continue,
             };
-            self.pending = Some(drain);
             self.active -= 1;
-            self.futures[id] = None;
-            return Some(ret)
+            self.futures[idx] = Slot::Next(self.next_future);
+            self.next_future = idx;
+            return Some(result)
         }
         None
     }
@@ -88,19 +147,21 @@ impl<F> Stream for FuturesUnordered<F>
     type Error = F::Error;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
-        if self.active == 0 {
-            return Ok(Async::Ready(None))
-        }
-        if let Some(drain) = self.pending.take() {
-            if let Some(ret) = self.poll_pending(drain) {
-                return ret
-            }
+        if let Some(ret) = self.poll_pending() {
+            return ret
         }
-        let drain = self.stack.drain();
-        if let Some(ret) = self.poll_pending(drain) {
+        self.pending = self.stack.drain();
+        if let Some(ret) = self.poll_pending() {
             return ret
         }
-        assert!(self.active > 0);
         Ok(Async::NotReady)
     }
 }
+
+impl<F> Default for FuturesUnordered<F>
+    where F: Future
+{
+    fn default() -> FuturesUnordered<F> {
+        FuturesUnordered::new()
+    }
+}
@@ -0,0 +1,56 @@
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which resolves to the item of a stream with the maximum key.
+///
+/// This future is created by the `Stream::max_by_key` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct MaxByKey<S, F, B> where S: Stream {
+    stream: S,
+    f: F,
+    max: Option<(B, S::Item)>,
+}
+
+pub fn new<S, F, B>(s: S, f: F) -> MaxByKey<S, F, B>
+    where S: Stream,
+          F: FnMut(&S::Item) -> B,
+          B: Ord,
+{
+    MaxByKey {
+        stream: s,
+        f: f,
+        max: None,
+    }
+}
+
+impl<S, F, B> Future for MaxByKey<S, F, B>
+    where S: Stream,
+          F: FnMut(&S::Item) -> B,
+          B: Ord,
+{
+    type Item = Option<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(e))) => {
+                    let key = (self.f)(&e);
+                    // Replace equal keys too so the last maximum is kept, like
+                    // `Iterator::max_by_key`.
+                    if self.max.as_ref().map(|m| key >= m.0).unwrap_or(true) {
+                        self.max = Some((key, e));
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    return Ok(Async::Ready(self.max.take().map(|m| m.1)))
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.max = None;
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use {Future, Poll, Async};
+use stream::Stream;
+
+/// A future which resolves to the item of a stream with the minimum key.
+///
+/// This future is created by the `Stream::min_by_key` method.
+#[must_use = "futures do nothing unless polled"]
+pub struct MinByKey<S, F, B> where S: Stream {
+    stream: S,
+    f: F,
+    min: Option<(B, S::Item)>,
+}
+
+pub fn new<S, F, B>(s: S, f: F) -> MinByKey<S, F, B>
+    where S: Stream,
+          F: FnMut(&S::Item) -> B,
+          B: Ord,
+{
+    MinByKey {
+        stream: s,
+        f: f,
+        min: None,
+    }
+}
+
+impl<S, F, B> Future for MinByKey<S, F, B>
+    where S: Stream,
+          F: FnMut(&S::Item) -> B,
+          B: Ord,
+{
+    type Item = Option<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match self.stream.poll() {
+                Ok(Async::Ready(Some(e))) => {
+                    let key = (self.f)(&e);
+                    // Only replace strictly smaller keys so the first minimum
+                    // is kept, like `Iterator::min_by_key`.
+                    if self.min.as_ref().map(|m| key < m.0).unwrap_or(true) {
+                        self.min = Some((key, e));
+                    }
+                }
+                Ok(Async::Ready(None)) => {
+                    return Ok(Async::Ready(self.min.take().map(|m| m.1)))
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.min = None;
+                    return Err(e)
+                }
+            }
+        }
+    }
+}
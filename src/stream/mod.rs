@@ -23,22 +23,33 @@ pub use self::iter::{iter, IterStream};
 mod repeat;
 pub use self::repeat::{repeat, Repeat};
 
+mod all;
 mod and_then;
+mod any;
 mod chain;
+mod concat;
+mod count;
 mod empty;
+mod enumerate;
 mod filter;
 mod filter_map;
+mod flat_map;
 mod flatten;
 mod fold;
 mod for_each;
 mod fuse;
 mod future;
+mod inspect;
+mod last;
 mod map;
 mod map_err;
+mod max_by_key;
 mod merge;
+mod min_by_key;
 mod once;
 mod or_else;
 mod peek;
+mod scan;
 mod select;
 mod skip;
 mod skip_while;
@@ -48,22 +59,33 @@ mod then;
 mod unfold;
 mod zip;
 mod forward;
+pub use self::all::All;
 pub use self::and_then::AndThen;
+pub use self::any::Any;
 pub use self::chain::Chain;
+pub use self::concat::Concat;
+pub use self::count::Count;
 pub use self::empty::{Empty, empty};
+pub use self::enumerate::Enumerate;
 pub use self::filter::Filter;
 pub use self::filter_map::FilterMap;
+pub use self::flat_map::FlatMap;
 pub use self::flatten::Flatten;
 pub use self::fold::Fold;
 pub use self::for_each::ForEach;
 pub use self::fuse::Fuse;
 pub use self::future::StreamFuture;
+pub use self::inspect::Inspect;
+pub use self::last::Last;
 pub use self::map::Map;
 pub use self::map_err::MapErr;
+pub use self::max_by_key::MaxByKey;
 pub use self::merge::{Merge, MergedItem};
+pub use self::min_by_key::MinByKey;
 pub use self::once::{Once, once};
 pub use self::or_else::OrElse;
 pub use self::peek::Peekable;
+pub use self::scan::Scan;
 pub use self::select::Select;
 pub use self::skip::Skip;
 pub use self::skip_while::SkipWhile;
@@ -78,23 +100,42 @@ use sink::{Sink};
 if_std! {
     use std;
 
+    mod abortable;
     mod buffered;
     mod buffer_unordered;
     mod catch_unwind;
     mod chunks;
+    mod chunks_timeout;
     mod collect;
+    mod debounce;
+    mod for_each_concurrent;
     mod wait;
     mod channel;
     mod split;
     mod futures_unordered;
+    mod rate_limit;
+    mod sample;
+    mod select_all;
+    mod throttle;
+    mod timeout;
+    pub use self::abortable::abortable;
+    pub use future::{Abortable, AbortHandle, AbortRegistration, AbortError};
     pub use self::buffered::Buffered;
     pub use self::buffer_unordered::BufferUnordered;
     pub use self::catch_unwind::CatchUnwind;
     pub use self::chunks::Chunks;
+    pub use self::chunks_timeout::ChunksTimeout;
     pub use self::collect::Collect;
+    pub use self::debounce::Debounce;
+    pub use self::for_each_concurrent::ForEachConcurrent;
     pub use self::wait::Wait;
     pub use self::split::{SplitStream, SplitSink};
     pub use self::futures_unordered::{futures_unordered, FuturesUnordered};
+    pub use self::rate_limit::RateLimit;
+    pub use self::sample::Sample;
+    pub use self::select_all::{select_all, SelectAll};
+    pub use self::throttle::Throttle;
+    pub use self::timeout::Timeout;
 
     #[doc(hidden)]
     #[cfg(feature = "with-deprecated")]
@@ -314,6 +355,34 @@ pub trait Stream {
         map_err::new(self, f)
     }
 
+    /// Do something with each item of this stream, afterwards passing it on.
+    ///
+    /// This is similar to the `Iterator::inspect` method in the standard
+    /// library where it allows easily inspecting each value as it passes
+    /// through the stream, for example to debug what's going on. Errors are
+    /// passed through without calling the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{Future, Stream};
+    /// use futures::stream;
+    ///
+    /// let mut seen = Vec::new();
+    /// let items = stream::iter(vec![Ok::<u32, ()>(1), Ok(2)])
+    ///     .inspect(|x| seen.push(*x))
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![1, 2]));
+    /// assert_eq!(seen, [1, 2]);
+    /// ```
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+        where F: FnMut(&Self::Item),
+              Self: Sized
+    {
+        inspect::new(self, f)
+    }
+
     /// Filters the values produced by this stream according to the provided
     /// predicate.
     ///
@@ -561,6 +630,166 @@ pub trait Stream {
         fold::new(self, f, init)
     }
 
+    /// Counts the number of items in this stream.
+    ///
+    /// The returned future resolves to the number of items once the stream is
+    /// finished. If an error happens then it will be returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let number_stream = stream::iter::<_, _, ()>((0..6).map(Ok));
+    /// assert_eq!(number_stream.count().wait(), Ok(6));
+    /// ```
+    fn count(self) -> Count<Self>
+        where Self: Sized
+    {
+        count::new(self)
+    }
+
+    /// Tests whether any item of this stream matches a predicate.
+    ///
+    /// The returned future resolves to `true` as soon as the predicate returns
+    /// `true` for an item, at which point the stream is dropped without being
+    /// polled any further. If the stream finishes first then the future
+    /// resolves to `false`, and if an error happens it will be returned
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let number_stream = stream::iter::<_, _, ()>((0..6).map(Ok));
+    /// assert_eq!(number_stream.any(|i| i == 3).wait(), Ok(true));
+    /// ```
+    fn any<P>(self, pred: P) -> Any<Self, P>
+        where P: FnMut(Self::Item) -> bool,
+              Self: Sized
+    {
+        any::new(self, pred)
+    }
+
+    /// Tests whether every item of this stream matches a predicate.
+    ///
+    /// The returned future resolves to `false` as soon as the predicate
+    /// returns `false` for an item, at which point the stream is dropped
+    /// without being polled any further. If the stream finishes first then
+    /// the future resolves to `true`, and if an error happens it will be
+    /// returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let number_stream = stream::iter::<_, _, ()>((0..6).map(Ok));
+    /// assert_eq!(number_stream.all(|i| i < 6).wait(), Ok(true));
+    /// ```
+    fn all<P>(self, pred: P) -> All<Self, P>
+        where P: FnMut(Self::Item) -> bool,
+              Self: Sized
+    {
+        all::new(self, pred)
+    }
+
+    /// Resolves to the last item of this stream, or `None` if it's empty.
+    ///
+    /// If an error happens then it will be returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let number_stream = stream::iter::<_, _, ()>((0..6).map(Ok));
+    /// assert_eq!(number_stream.last().wait(), Ok(Some(5)));
+    /// ```
+    fn last(self) -> Last<Self>
+        where Self: Sized
+    {
+        last::new(self)
+    }
+
+    /// Resolves to the item of this stream which gives the minimum value from
+    /// the function `f`, or `None` if the stream is empty.
+    ///
+    /// If several items are equally minimum then the first one is returned.
+    /// If an error happens then it will be returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let stream = stream::iter::<_, _, ()>(vec![Ok(-3i32), Ok(1), Ok(-2)]);
+    /// assert_eq!(stream.min_by_key(|x| x.abs()).wait(), Ok(Some(1)));
+    /// ```
+    fn min_by_key<B, F>(self, f: F) -> MinByKey<Self, F, B>
+        where F: FnMut(&Self::Item) -> B,
+              B: Ord,
+              Self: Sized
+    {
+        min_by_key::new(self, f)
+    }
+
+    /// Resolves to the item of this stream which gives the maximum value from
+    /// the function `f`, or `None` if the stream is empty.
+    ///
+    /// If several items are equally maximum then the last one is returned.
+    /// If an error happens then it will be returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let stream = stream::iter::<_, _, ()>(vec![Ok(-3i32), Ok(1), Ok(-2)]);
+    /// assert_eq!(stream.max_by_key(|x| x.abs()).wait(), Ok(Some(-3)));
+    /// ```
+    fn max_by_key<B, F>(self, f: F) -> MaxByKey<Self, F, B>
+        where F: FnMut(&Self::Item) -> B,
+              B: Ord,
+              Self: Sized
+    {
+        max_by_key::new(self, f)
+    }
+
+    /// Concatenates all items of this stream into a single value.
+    ///
+    /// The first item of the stream is used as the accumulator, and all
+    /// following items are added to it through `Extend`. This makes it easy to
+    /// join a stream of chunks, such as `Vec<u8>`, without copying the first
+    /// chunk. If the stream is empty then the future resolves to
+    /// `Default::default()`.
+    ///
+    /// If an error happens then the accumulated value is dropped and the error
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let chunks = stream::iter::<_, _, ()>(vec![Ok(vec![1, 2]), Ok(vec![3])]);
+    /// assert_eq!(chunks.concat().wait(), Ok(vec![1, 2, 3]));
+    /// ```
+    fn concat(self) -> Concat<Self>
+        where Self::Item: Extend<<Self::Item as IntoIterator>::Item> + IntoIterator + Default,
+              Self: Sized
+    {
+        concat::new(self)
+    }
+
     /// Flattens a stream of streams into just one continuous stream.
     ///
     /// If this stream's elements are themselves streams then this combinator
@@ -602,6 +831,88 @@ pub trait Stream {
         flatten::new(self)
     }
 
+    /// Maps each item of this stream to a stream, and flattens the result
+    /// into one long stream of elements.
+    ///
+    /// This is equivalent to `map` followed by `flatten`, and like
+    /// `Iterator::flat_map` each stream produced by the closure is run to
+    /// completion before the next item of this stream is pulled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{Future, Stream};
+    /// use futures::stream;
+    ///
+    /// let items = stream::iter(vec![Ok::<u32, ()>(1), Ok(2)])
+    ///     .flat_map(|x| stream::iter((0..x).map(Ok::<u32, ()>)))
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![0, 0, 1]));
+    /// ```
+    fn flat_map<F, U>(self, f: F) -> FlatMap<Self, F, U>
+        where F: FnMut(Self::Item) -> U,
+              U: Stream,
+              U::Error: From<Self::Error>,
+              Self: Sized
+    {
+        flat_map::new(self, f)
+    }
+
+    /// Creates a stream which yields the current index along with each item.
+    ///
+    /// The index starts at zero and is only incremented for successful items,
+    /// errors are passed through without affecting it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{Future, Stream};
+    /// use futures::stream;
+    ///
+    /// let items = stream::iter(vec![Ok::<char, ()>('a'), Ok('b')])
+    ///     .enumerate()
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(items, Ok(vec![(0, 'a'), (1, 'b')]));
+    /// ```
+    fn enumerate(self) -> Enumerate<Self>
+        where Self: Sized
+    {
+        enumerate::new(self)
+    }
+
+    /// Creates a stream which threads a mutable state through a closure
+    /// applied to each item.
+    ///
+    /// This is similar to `Iterator::scan`: the closure is given a mutable
+    /// reference to the state along with each item, and returns the next item
+    /// of the new stream. Once the closure returns `None` the new stream
+    /// finishes, and the underlying stream is no longer polled. Errors are
+    /// passed through without calling the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::{Future, Stream};
+    /// use futures::stream;
+    ///
+    /// let sums = stream::iter(vec![Ok::<u32, ()>(1), Ok(2), Ok(3), Ok(4)])
+    ///     .scan(0, |sum, x| {
+    ///         *sum += x;
+    ///         if *sum > 6 { None } else { Some(*sum) }
+    ///     })
+    ///     .collect()
+    ///     .wait();
+    /// assert_eq!(sums, Ok(vec![1, 3, 6]));
+    /// ```
+    fn scan<St, F, U>(self, initial_state: St, f: F) -> Scan<Self, St, F>
+        where F: FnMut(&mut St, Self::Item) -> Option<U>,
+              Self: Sized
+    {
+        scan::new(self, initial_state, f)
+    }
+
     /// Skip elements on this stream while the predicate provided resolves to
     /// `true`.
     ///
@@ -650,6 +961,51 @@ pub trait Stream {
         for_each::new(self, f)
     }
 
+    /// Runs this stream to completion, executing the provided closure for each
+    /// element on the stream with up to `limit` of the resulting futures
+    /// running concurrently.
+    ///
+    /// This is similar to `for_each`, except that a new item is pulled off the
+    /// stream as soon as fewer than `limit` futures are in flight, rather
+    /// than waiting for the previous future to complete. The returned future
+    /// resolves once the stream has finished and all of the futures have
+    /// completed.
+    ///
+    /// If the stream or any of the futures returns an error then the stream
+    /// and all other in-flight futures are dropped, and the returned future
+    /// resolves to that error.
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use futures::stream::{self, Stream};
+    /// use futures::future::Future;
+    ///
+    /// let sum = AtomicUsize::new(0);
+    /// let jobs = stream::iter::<_, _, ()>((1..5).map(Ok));
+    /// jobs.for_each_concurrent(2, |i| {
+    ///     sum.fetch_add(i, Ordering::SeqCst);
+    ///     Ok(())
+    /// }).wait().unwrap();
+    /// assert_eq!(sum.load(Ordering::SeqCst), 10);
+    /// ```
+    #[cfg(feature = "use_std")]
+    fn for_each_concurrent<F, U>(self, limit: usize, f: F) -> ForEachConcurrent<Self, F, U>
+        where F: FnMut(Self::Item) -> U,
+              U: IntoFuture<Item = (), Error = Self::Error>,
+              Self: Sized
+    {
+        for_each_concurrent::new(self, limit, f)
+    }
+
     /// Creates a new stream of at most `amt` items of the underlying stream.
     ///
     /// Once `amt` items have been yielded from this stream then it will always
@@ -924,6 +1280,118 @@ pub trait Stream {
     {
         split::split(self)
     }
+
+    /// Fails this stream with `TimeoutError::Elapsed` if no item is produced
+    /// within `dur` of the previous one.
+    ///
+    /// The deadline is tracked by the default timer (see `Timer::default`),
+    /// and is reset every time an item or error is yielded. After the timeout
+    /// error has been returned the stream may continue to be polled, in which
+    /// case a fresh deadline starts. Errors from the underlying stream are
+    /// passed through as `TimeoutError::Inner`.
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "use_std")]
+    fn timeout(self, dur: std::time::Duration) -> Timeout<Self>
+        where Self: Sized
+    {
+        timeout::new(self, dur)
+    }
+
+    /// Like `chunks`, but also yields a partial chunk once `dur` has elapsed
+    /// since its first item was received.
+    ///
+    /// This is useful for batching items without holding on to them for too
+    /// long when the stream is slow. The deadline is tracked by the default
+    /// timer (see `Timer::default`). Errors are handled as with `chunks`.
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is zero.
+    #[cfg(feature = "use_std")]
+    fn chunks_timeout(self, capacity: usize, dur: std::time::Duration) -> ChunksTimeout<Self>
+        where Self: Sized
+    {
+        chunks_timeout::new(self, capacity, dur)
+    }
+
+    /// Yields at most one item per `dur`, discarding the items which arrive
+    /// in between.
+    ///
+    /// The first item is always yielded, after which any item produced within
+    /// `dur` of the last yielded one is dropped. Errors are passed through
+    /// immediately. The time is read from the default timer (see
+    /// `Timer::default`).
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "use_std")]
+    fn throttle(self, dur: std::time::Duration) -> Throttle<Self>
+        where Self: Sized
+    {
+        throttle::new(self, dur)
+    }
+
+    /// Yields an item only once `dur` has passed without the stream producing
+    /// a newer one.
+    ///
+    /// Each item replaces any item still waiting to be yielded and restarts
+    /// the wait, so bursts of items are collapsed to their last one. When the
+    /// underlying stream finishes the waiting item is yielded straight away.
+    /// Errors are passed through immediately. The wait is tracked by the
+    /// default timer (see `Timer::default`).
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "use_std")]
+    fn debounce(self, dur: std::time::Duration) -> Debounce<Self>
+        where Self: Sized
+    {
+        debounce::new(self, dur)
+    }
+
+    /// Yields the latest item of this stream once every `interval`.
+    ///
+    /// Older items received within the same interval are discarded, and no
+    /// item is yielded for an interval in which the stream didn't produce one.
+    /// When the underlying stream finishes, its last item is yielded straight
+    /// away if it hasn't been yet. Errors are passed through immediately. The
+    /// interval is tracked by the default timer (see `Timer::default`).
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    #[cfg(feature = "use_std")]
+    fn sample(self, interval: std::time::Duration) -> Sample<Self>
+        where Self: Sized
+    {
+        sample::new(self, interval)
+    }
+
+    /// Limits the rate at which this stream yields items to `n` items per
+    /// `per`.
+    ///
+    /// The limit is implemented as a token bucket: up to `n` items may be
+    /// yielded back to back (see `RateLimit::burst` to change this), after
+    /// which the underlying stream isn't polled again until the next item is
+    /// allowed through. No items are dropped. The time is tracked by the
+    /// default timer (see `Timer::default`).
+    ///
+    /// This method is only available when the `use_std` feature of this
+    /// library is activated, and it is activated by default.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `n` is zero.
+    #[cfg(feature = "use_std")]
+    fn rate_limit(self, n: usize, per: std::time::Duration) -> RateLimit<Self>
+        where Self: Sized
+    {
+        rate_limit::new(self, n, per)
+    }
 }
 
 impl<'a, S: ?Sized + Stream> Stream for &'a mut S {
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use {Async, Poll};
+use stream::Stream;
+use token_bucket::TokenBucket;
+
+/// A stream combinator which limits the rate at which items are yielded.
+///
+/// This is created by the `Stream::rate_limit` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct RateLimit<S> {
+    stream: S,
+    bucket: TokenBucket,
+}
+
+pub fn new<S>(stream: S, n: usize, per: Duration) -> RateLimit<S>
+    where S: Stream,
+{
+    RateLimit {
+        stream: stream,
+        bucket: TokenBucket::new(n, per),
+    }
+}
+
+impl<S> RateLimit<S> {
+    /// Sets the number of items which may be yielded back to back after the
+    /// stream has been idle, which defaults to the `n` given to `rate_limit`.
+    ///
+    /// The average rate stays the same regardless of the burst size.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `burst` is zero.
+    pub fn burst(mut self, burst: usize) -> RateLimit<S> {
+        self.bucket.set_burst(burst);
+        self
+    }
+
+    /// Acquires a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+impl<S> ::sink::Sink for RateLimit<S>
+    where S: ::sink::Sink
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> ::StartSend<S::SinkItem, S::SinkError> {
+        self.stream.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        self.stream.poll_complete()
+    }
+}
+
+impl<S> Stream for RateLimit<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        if let Async::NotReady = self.bucket.poll_ready() {
+            return Ok(Async::NotReady)
+        }
+        let item = try_ready!(self.stream.poll());
+        // Only items use up tokens, errors and the end of the stream don't
+        if item.is_some() {
+            self.bucket.take();
+        }
+        Ok(Async::Ready(item))
+    }
+}
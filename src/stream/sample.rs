@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use {Async, Future, Poll};
+use stream::{Stream, Fuse};
+use timer::{Delay, Timer};
+
+/// A stream combinator which yields the latest item of the underlying stream
+/// once per period.
+///
+/// This is created by the `Stream::sample` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Sample<S> where S: Stream {
+    stream: Fuse<S>,
+    // Fires at the end of the current period
+    delay: Delay,
+    dur: Duration,
+    // The latest item which hasn't been yielded yet
+    latest: Option<S::Item>,
+}
+
+pub fn new<S>(stream: S, dur: Duration) -> Sample<S>
+    where S: Stream,
+{
+    Sample {
+        stream: super::fuse::new(stream),
+        delay: Timer::default().delay(dur),
+        dur: dur,
+        latest: None,
+    }
+}
+
+impl<S> Stream for Sample<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match try!(self.stream.poll()) {
+                Async::Ready(Some(item)) => self.latest = Some(item),
+                // Flush the last item, if it hasn't been seen, and finish
+                Async::Ready(None) => return Ok(Async::Ready(self.latest.take())),
+                Async::NotReady => break,
+            }
+        }
+
+        loop {
+            match self.delay.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(())) | Err(()) => {
+                    // If we've missed more than one tick, start a new period
+                    // from now rather than working through every missed one.
+                    let now = self.delay.timer().now();
+                    let mut next = self.delay.deadline() + self.dur;
+                    if next <= now {
+                        next = now + self.dur;
+                    }
+                    self.delay.reset(next);
+
+                    // Ticks without a new item in between are skipped
+                    if let Some(item) = self.latest.take() {
+                        return Ok(Async::Ready(Some(item)))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use {Async, Poll};
+use stream::Stream;
+
+/// A stream combinator which threads state through a closure applied to each
+/// item, ending the stream once the closure returns `None`.
+///
+/// This is produced by the `Stream::scan` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Scan<S, St, F> {
+    stream: S,
+    state: St,
+    f: F,
+    done: bool,
+}
+
+pub fn new<S, St, F, U>(s: S, initial_state: St, f: F) -> Scan<S, St, F>
+    where S: Stream,
+          F: FnMut(&mut St, S::Item) -> Option<U>,
+{
+    Scan {
+        stream: s,
+        state: initial_state,
+        f: f,
+        done: false,
+    }
+}
+
+// Forwarding impl of Sink from the underlying stream
+impl<S, St, F> ::sink::Sink for Scan<S, St, F>
+    where S: ::sink::Sink
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> ::StartSend<S::SinkItem, S::SinkError> {
+        self.stream.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), S::SinkError> {
+        self.stream.poll_complete()
+    }
+}
+
+impl<S, St, F, U> Stream for Scan<S, St, F>
+    where S: Stream,
+          F: FnMut(&mut St, S::Item) -> Option<U>,
+{
+    type Item = U;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<U>, S::Error> {
+        if self.done {
+            return Ok(Async::Ready(None))
+        }
+        let item = match try_ready!(self.stream.poll()) {
+            Some(item) => (self.f)(&mut self.state, item),
+            None => None,
+        };
+        if item.is_none() {
+            self.done = true;
+        }
+        Ok(Async::Ready(item))
+    }
+}
@@ -0,0 +1,122 @@
+use std::prelude::v1::*;
+
+use {Async, Poll};
+use stream::{Stream, StreamFuture, FuturesUnordered};
+
+/// An unbounded set of streams whose items are merged into one stream.
+///
+/// Items are yielded in the order that they become available on the
+/// underlying streams. Only streams which have been woken up since they were
+/// last polled are polled again, so this scales to a large number of mostly
+/// idle streams. Futures can be added to the set by first converting them
+/// with `Future::into_stream`.
+///
+/// A stream which produces an error stays in the set, while one which finishes
+/// is removed from it. Like `FuturesUnordered`, an empty set is not considered
+/// finished: polling it will return `NotReady` as more streams may be pushed
+/// later.
+///
+/// This is created by the `select_all` function or by `SelectAll::new`.
+#[must_use = "streams do nothing unless polled"]
+pub struct SelectAll<S>
+    where S: Stream
+{
+    // Each stream is driven as a `StreamFuture` which resolves to its next
+    // item, and is pushed back into the set once it has yielded one. This way
+    // only streams which have been woken up are polled again.
+    inner: FuturesUnordered<StreamFuture<S>>,
+}
+
+/// Converts a list of streams into a single stream merging all of their items.
+///
+/// More streams can be added later on with `SelectAll::push`.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{Future, Stream};
+/// use futures::stream;
+///
+/// let all = stream::select_all(vec![
+///     stream::iter(vec![Ok::<u32, ()>(1)]),
+///     stream::iter(vec![Ok::<u32, ()>(2)]),
+/// ]);
+///
+/// // The set never finishes on its own, so only take the items we expect
+/// let mut items = all.take(2).collect().wait().unwrap();
+/// items.sort();
+/// assert_eq!(items, [1, 2]);
+/// ```
+pub fn select_all<I>(streams: I) -> SelectAll<I::Item>
+    where I: IntoIterator,
+          I::Item: Stream,
+{
+    let mut set = SelectAll::new();
+    for stream in streams {
+        set.push(stream);
+    }
+    set
+}
+
+impl<S: Stream> SelectAll<S> {
+    /// Constructs a new, empty `SelectAll`.
+    ///
+    /// The returned set does not contain any streams, and in this state
+    /// polling it will return `NotReady`. Streams can be added with `push`.
+    pub fn new() -> SelectAll<S> {
+        SelectAll { inner: FuturesUnordered::new() }
+    }
+
+    /// Returns the number of streams contained in the set.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the set contains no streams.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Push a stream into the set.
+    ///
+    /// This function will not call `poll` on the submitted stream. The caller
+    /// must ensure that `SelectAll::poll` is called in order to receive its
+    /// items.
+    pub fn push(&mut self, stream: S) {
+        self.inner.push(stream.into_future());
+    }
+}
+
+impl<S: Stream> Stream for SelectAll<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            match self.inner.poll() {
+                // The stream may have more items ready without having
+                // scheduled a wakeup, so pushing it back means it's polled
+                // again, after the other streams woken so far.
+                Ok(Async::Ready(Some((Some(item), stream)))) => {
+                    self.push(stream);
+                    return Ok(Async::Ready(Some(item)))
+                }
+                Ok(Async::Ready(Some((None, _)))) => continue,
+                Err((e, stream)) => {
+                    self.push(stream);
+                    return Err(e)
+                }
+                // An empty `FuturesUnordered` isn't finished, and neither are
+                // we.
+                Ok(Async::Ready(None)) |
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+            }
+        }
+    }
+}
+
+impl<S: Stream> Default for SelectAll<S> {
+    fn default() -> SelectAll<S> {
+        SelectAll::new()
+    }
+}
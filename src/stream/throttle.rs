@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use {Async, Poll};
+use stream::Stream;
+use timer::Timer;
+
+/// A stream combinator which yields at most one item per period, discarding
+/// items which arrive in between.
+///
+/// This is created by the `Stream::throttle` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Throttle<S> {
+    stream: S,
+    timer: Timer,
+    dur: Duration,
+    // Items arriving before this instant are discarded
+    next: Option<Instant>,
+}
+
+pub fn new<S>(stream: S, dur: Duration) -> Throttle<S>
+    where S: Stream,
+{
+    Throttle {
+        stream: stream,
+        timer: Timer::default(),
+        dur: dur,
+        next: None,
+    }
+}
+
+impl<S> Throttle<S> {
+    /// Acquires a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for Throttle<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        loop {
+            let item = match try_ready!(self.stream.poll()) {
+                Some(item) => item,
+                None => return Ok(Async::Ready(None)),
+            };
+            let now = self.timer.now();
+            if self.next.map(|next| now >= next).unwrap_or(true) {
+                self.next = Some(now + self.dur);
+                return Ok(Async::Ready(Some(item)))
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use {Future, Poll, Async};
+use stream::Stream;
+use timer::{Delay, Timer, TimeoutError};
+
+/// A stream combinator which fails with `TimeoutError::Elapsed` if too much
+/// time passes between items.
+///
+/// This is created by the `Stream::timeout` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Timeout<S> {
+    stream: S,
+    delay: Delay,
+    dur: Duration,
+}
+
+pub fn new<S>(stream: S, dur: Duration) -> Timeout<S>
+    where S: Stream,
+{
+    Timeout {
+        stream: stream,
+        delay: Timer::default().delay(dur),
+        dur: dur,
+    }
+}
+
+impl<S> Timeout<S> {
+    /// Acquires a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Acquires a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes this combinator, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn reset(&mut self) {
+        let next = self.delay.timer().now() + self.dur;
+        self.delay.reset(next);
+    }
+}
+
+impl<S> Stream for Timeout<S>
+    where S: Stream,
+{
+    type Item = S::Item;
+    type Error = TimeoutError<S::Error>;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, TimeoutError<S::Error>> {
+        match self.stream.poll() {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(e)) => {
+                self.reset();
+                return Ok(Async::Ready(e))
+            }
+            Err(e) => {
+                self.reset();
+                return Err(TimeoutError::Inner(e))
+            }
+        }
+
+        // Once the deadline passes an error is yielded, after which the
+        // stream may continue to be polled with a fresh deadline.
+        match self.delay.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(())) | Err(()) => {
+                self.reset();
+                Err(TimeoutError::Elapsed)
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! The bounded channel behind `mpmc` and `priority`, which only differ in the
+//! order that they yield messages in.
+//!
+//! The channel capacity is `buffer + num-senders`, as with `mpsc::channel`: a
+//! sender which fills up the channel is parked until a receiver takes a
+//! message out of it.
+
+use std::prelude::v1::*;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use task::{self, Task};
+use {Async, AsyncSink, Poll, StartSend};
+
+/// The messages buffered in a channel, which decides the order that they're
+/// received in.
+pub trait Queue: Default {
+    /// The type of messages sent into the queue.
+    type Item;
+
+    /// The type of messages received from the queue.
+    type Output;
+
+    /// Adds a message to the queue.
+    fn push(&mut self, item: Self::Item);
+
+    /// Removes the next message to be received from the queue.
+    fn pop(&mut self) -> Option<Self::Output>;
+
+    /// Returns the number of messages in the queue.
+    fn len(&self) -> usize;
+
+    /// Returns whether the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Queue for VecDeque<T> {
+    type Item = T;
+    type Output = T;
+
+    fn push(&mut self, item: T) {
+        self.push_back(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+}
+
+pub struct Sender<Q: Queue> {
+    inner: Arc<Inner<Q>>,
+    id: usize,
+}
+
+pub struct Receiver<Q: Queue> {
+    inner: Arc<Inner<Q>>,
+    id: usize,
+}
+
+/// Error type for sending, used when all receivers have been dropped or the
+/// channel has been closed.
+///
+/// The message that failed to be sent is returned along with the error.
+pub struct SendError<T>(T);
+
+struct Inner<Q> {
+    buffer: usize,
+    state: Mutex<State<Q>>,
+}
+
+struct State<Q> {
+    messages: Q,
+
+    // `false` once all receivers are gone or `Receiver::close` was called
+    is_open: bool,
+
+    num_senders: usize,
+    num_receivers: usize,
+
+    // Senders which filled up the channel, in the order that they did so. A
+    // sender stays parked until a receiver takes a message and pops it off
+    // this queue.
+    parked_senders: VecDeque<(usize, Task)>,
+
+    // Receivers waiting for a message, in the order that they started waiting
+    waiting_receivers: VecDeque<(usize, Task)>,
+
+    next_id: usize,
+}
+
+pub fn channel<Q: Queue>(buffer: usize) -> (Sender<Q>, Receiver<Q>) {
+    let inner = Arc::new(Inner {
+        buffer: buffer,
+        state: Mutex::new(State {
+            messages: Q::default(),
+            is_open: true,
+            num_senders: 1,
+            num_receivers: 1,
+            parked_senders: VecDeque::new(),
+            waiting_receivers: VecDeque::new(),
+            next_id: 2,
+        }),
+    });
+    let tx = Sender { inner: inner.clone(), id: 0 };
+    let rx = Receiver { inner: inner, id: 1 };
+    (tx, rx)
+}
+
+impl<Q> State<Q> {
+    fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+
+    // Takes the task of the first receiver waiting for a message, which should
+    // be unparked after releasing the state lock.
+    fn wake_receiver(&mut self) -> Option<Task> {
+        self.waiting_receivers.pop_front().map(|(_, task)| task)
+    }
+
+    // Takes the tasks of all parked senders and waiting receivers, which should
+    // be unparked after releasing the state lock.
+    fn wake_all(&mut self) -> Vec<Task> {
+        let senders = self.parked_senders.drain(..).map(|(_, task)| task);
+        let receivers = self.waiting_receivers.drain(..).map(|(_, task)| task);
+        senders.chain(receivers).collect()
+    }
+}
+
+impl<Q: Queue> Sender<Q> {
+    fn poll_unparked(&self, state: &mut State<Q>) -> Async<()> {
+        for &mut (id, ref mut task) in state.parked_senders.iter_mut() {
+            if id == self.id {
+                // Update the task in case the `Sender` has been moved to
+                // another task
+                *task = task::park();
+                return Async::NotReady
+            }
+        }
+        Async::Ready(())
+    }
+
+    pub fn start_send(&mut self, msg: Q::Item) -> StartSend<Q::Item, SendError<Q::Item>> {
+        let task = {
+            let mut state = self.inner.state.lock().unwrap();
+            if !state.is_open {
+                return Err(SendError(msg))
+            }
+
+            // If the sender is currently blocked, reject the message before
+            // doing any work.
+            if !self.poll_unparked(&mut state).is_ready() {
+                return Ok(AsyncSink::NotReady(msg))
+            }
+
+            state.messages.push(msg);
+            if state.messages.len() > self.inner.buffer {
+                state.parked_senders.push_back((self.id, task::park()));
+            }
+            state.wake_receiver()
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+        Ok(AsyncSink::Ready)
+    }
+}
+
+impl<Q: Queue> Clone for Sender<Q> {
+    fn clone(&self) -> Sender<Q> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.num_senders += 1;
+        Sender {
+            inner: self.inner.clone(),
+            id: state.next_id(),
+        }
+    }
+}
+
+impl<Q: Queue> Drop for Sender<Q> {
+    fn drop(&mut self) {
+        let tasks = {
+            let mut state = self.inner.state.lock().unwrap();
+            let id = self.id;
+            state.parked_senders.retain(|&(other, _)| other != id);
+            state.num_senders -= 1;
+            if state.num_senders > 0 {
+                return
+            }
+            // Wake up all receivers as they'll see that there are no more
+            // senders once they've drained the channel.
+            state.wake_all()
+        };
+        for task in tasks {
+            task.unpark();
+        }
+    }
+}
+
+impl<Q: Queue> Receiver<Q> {
+    pub fn close(&mut self) {
+        let tasks = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.is_open = false;
+            state.wake_all()
+        };
+        for task in tasks {
+            task.unpark();
+        }
+    }
+
+    pub fn poll(&mut self) -> Poll<Option<Q::Output>, ()> {
+        let (msg, sender, receiver) = {
+            let mut state = self.inner.state.lock().unwrap();
+            let id = self.id;
+            state.waiting_receivers.retain(|&(other, _)| other != id);
+
+            let msg = match state.messages.pop() {
+                Some(msg) => msg,
+                None => {
+                    if state.num_senders == 0 || !state.is_open {
+                        return Ok(Async::Ready(None))
+                    }
+                    state.waiting_receivers.push_back((id, task::park()));
+                    return Ok(Async::NotReady)
+                }
+            };
+
+            // Make room for one parked sender, and if there are more messages
+            // hand them to another receiver in case we don't come back soon.
+            let sender = state.parked_senders.pop_front().map(|(_, task)| task);
+            let receiver = if state.messages.is_empty() {
+                None
+            } else {
+                state.wake_receiver()
+            };
+            (msg, sender, receiver)
+        };
+        for task in sender.into_iter().chain(receiver) {
+            task.unpark();
+        }
+        Ok(Async::Ready(Some(msg)))
+    }
+}
+
+impl<Q: Queue> Clone for Receiver<Q> {
+    fn clone(&self) -> Receiver<Q> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.num_receivers += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            id: state.next_id(),
+        }
+    }
+}
+
+impl<Q: Queue> Drop for Receiver<Q> {
+    fn drop(&mut self) {
+        let (tasks, messages) = {
+            let mut state = self.inner.state.lock().unwrap();
+            let id = self.id;
+            state.waiting_receivers.retain(|&(other, _)| other != id);
+            state.num_receivers -= 1;
+            if state.num_receivers > 0 {
+                // We may have been woken up for a message we'll now never
+                // take, so pass that on to another receiver.
+                let task = if state.messages.is_empty() {
+                    None
+                } else {
+                    state.wake_receiver()
+                };
+                (task.into_iter().collect(), Q::default())
+            } else {
+                // Nobody is left to read the buffered messages, so drop them
+                // outside of the lock and let the senders know.
+                state.is_open = false;
+                (state.wake_all(), mem::replace(&mut state.messages, Q::default()))
+            }
+        };
+        drop(messages);
+        for task in tasks {
+            task.unpark();
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("SendError")
+            .field(&"...")
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "send failed because all receivers are gone")
+    }
+}
+
+impl<T> Error for SendError<T>
+    where T: Any
+{
+    fn description(&self) -> &str {
+        "send failed because all receivers are gone"
+    }
+}
+
+impl<T> SendError<T> {
+    /// Returns the message that was attempted to be sent but failed.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
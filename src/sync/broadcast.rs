@@ -0,0 +1,309 @@
+//! A multi-producer, multi-consumer broadcast channel.
+//!
+//! Every message sent on a broadcast channel is delivered to every `Receiver`
+//! which was subscribed at the time it was sent. Messages are kept in a ring
+//! buffer of a fixed capacity which all receivers share, and each receiver
+//! tracks its own position in that buffer.
+//!
+//! Senders never wait for receivers. If a receiver falls so far behind that
+//! the messages it hasn't seen yet have been overwritten, it's notified with
+//! a `RecvError::Lagged` error carrying the number of messages it missed, and
+//! then continues from the oldest message still in the buffer.
+//!
+//! # Disconnection
+//!
+//! When all `Sender` handles have been dropped, receivers will yield the
+//! remaining messages in the buffer and then terminate. If all receivers have
+//! been dropped then sending will fail, although a new receiver can always be
+//! created with `Sender::subscribe`.
+
+use std::prelude::v1::*;
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use task::{self, Task};
+use {Async, AsyncSink, Poll, StartSend, Sink, Stream};
+
+/// The transmission end of a broadcast channel which is used to send values
+/// to all receivers.
+///
+/// This is created by the `channel` method.
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The receiving end of a broadcast channel which implements the `Stream`
+/// trait.
+///
+/// Cloning a receiver creates a new receiver at the same position in the
+/// channel. This is created by the `channel` method or by
+/// `Sender::subscribe`.
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+    id: usize,
+    // The position of the next message this receiver will yield
+    next: u64,
+}
+
+fn _assert_kinds() {
+    fn _assert_send<T: Send>() {}
+    fn _assert_sync<T: Sync>() {}
+    _assert_send::<Sender<u32>>();
+    _assert_sync::<Sender<u32>>();
+    _assert_send::<Receiver<u32>>();
+}
+
+/// Error type for sending, used when all receivers have been dropped.
+///
+/// The message that failed to be sent is returned along with the error.
+pub struct SendError<T>(T);
+
+/// Error type yielded by a `Receiver` which has fallen behind its senders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// The receiver missed the given number of messages, which were
+    /// overwritten before it read them. The next item yielded will be the
+    /// oldest message still in the channel.
+    Lagged(u64),
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    // Ring buffer of the last `buffer.len()` messages sent. The message with
+    // position `pos` lives at index `pos % buffer.len()`.
+    buffer: Vec<Option<T>>,
+
+    // Position of the next message to be sent
+    tail: u64,
+
+    num_senders: usize,
+    num_receivers: usize,
+
+    // Receivers waiting for a new message, keyed by receiver id
+    next_id: usize,
+    waiting: HashMap<usize, Task>,
+}
+
+/// Creates a new broadcast channel which holds up to `capacity` messages,
+/// returning the sender and receiver halves.
+///
+/// Further receivers can be created with `Sender::subscribe`, and will only
+/// see messages sent after they were created.
+///
+/// # Panics
+///
+/// This function panics if `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "capacity cannot be zero");
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State {
+            buffer: (0..capacity).map(|_| None).collect(),
+            tail: 0,
+            num_senders: 1,
+            num_receivers: 0,
+            next_id: 0,
+            waiting: HashMap::new(),
+        }),
+    });
+    let tx = Sender { inner: inner };
+    let rx = tx.subscribe();
+    (tx, rx)
+}
+
+impl<T> Sender<T> {
+    /// Sends a message to every receiver currently subscribed.
+    ///
+    /// This never waits for receivers; if the buffer is full the oldest
+    /// message is overwritten. An error is returned, along with the message,
+    /// if there are no receivers.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let waiting = {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.num_receivers == 0 {
+                return Err(SendError(msg))
+            }
+            let idx = (state.tail % state.buffer.len() as u64) as usize;
+            state.buffer[idx] = Some(msg);
+            state.tail += 1;
+            state.waiting.drain().map(|(_, task)| task).collect::<Vec<_>>()
+        };
+        for task in waiting {
+            task.unpark();
+        }
+        Ok(())
+    }
+
+    /// Creates a new receiver which will receive all messages sent after this
+    /// call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.num_receivers += 1;
+        let id = state.next_id;
+        state.next_id += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            id: id,
+            next: state.tail,
+        }
+    }
+}
+
+impl<T> Sink for Sender<T> {
+    type SinkItem = T;
+    type SinkError = SendError<T>;
+
+    fn start_send(&mut self, msg: T) -> StartSend<T, SendError<T>> {
+        try!(Sender::send(self, msg));
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<T>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<'a, T> Sink for &'a Sender<T> {
+    type SinkItem = T;
+    type SinkError = SendError<T>;
+
+    fn start_send(&mut self, msg: T) -> StartSend<T, SendError<T>> {
+        try!(Sender::send(self, msg));
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<T>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.inner.state.lock().unwrap().num_senders += 1;
+        Sender { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waiting = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.num_senders -= 1;
+            if state.num_senders > 0 {
+                return
+            }
+            state.waiting.drain().map(|(_, task)| task).collect::<Vec<_>>()
+        };
+
+        // Wake up all receivers as they'll see that there are no more senders
+        // once they've drained the buffer.
+        for task in waiting {
+            task.unpark();
+        }
+    }
+}
+
+impl<T: Clone> Stream for Receiver<T> {
+    type Item = T;
+    type Error = RecvError;
+
+    fn poll(&mut self) -> Poll<Option<T>, RecvError> {
+        let mut state = self.inner.state.lock().unwrap();
+        if self.next == state.tail {
+            if state.num_senders == 0 {
+                return Ok(Async::Ready(None))
+            }
+            state.waiting.insert(self.id, task::park());
+            return Ok(Async::NotReady)
+        }
+
+        // If the messages we haven't seen yet have been overwritten, skip
+        // ahead to the oldest message still around.
+        let capacity = state.buffer.len() as u64;
+        if state.tail - self.next > capacity {
+            let missed = state.tail - capacity - self.next;
+            self.next = state.tail - capacity;
+            return Err(RecvError::Lagged(missed))
+        }
+
+        let idx = (self.next % capacity) as usize;
+        self.next += 1;
+        let msg = state.buffer[idx].clone().expect("message missing");
+        Ok(Async::Ready(Some(msg)))
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.num_receivers += 1;
+        let id = state.next_id;
+        state.next_id += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            id: id,
+            next: self.next,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.num_receivers -= 1;
+        state.waiting.remove(&self.id);
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("SendError")
+            .field(&"...")
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "send failed because all receivers are gone")
+    }
+}
+
+impl<T> Error for SendError<T>
+    where T: Any
+{
+    fn description(&self) -> &str {
+        "send failed because all receivers are gone"
+    }
+}
+
+impl<T> SendError<T> {
+    /// Returns the message that was attempted to be sent but failed.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvError::Lagged(n) => {
+                write!(fmt, "receiver lagged behind and missed {} messages", n)
+            }
+        }
+    }
+}
+
+impl Error for RecvError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvError::Lagged(_) => "receiver lagged behind",
+        }
+    }
+}
@@ -12,6 +12,19 @@
 
 pub mod oneshot;
 pub mod mpsc;
+pub mod mpmc;
+pub mod priority;
+pub mod broadcast;
+pub mod watch;
 mod bilock;
+mod bounded;
+mod mutex;
+mod rwlock;
+mod semaphore;
+mod wait_queue;
 
 pub use self::bilock::{BiLock, BiLockGuard, BiLockAcquire, BiLockAcquired};
+pub use self::mutex::{Mutex, MutexGuard, MutexAcquire};
+pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use self::rwlock::{RwLockReadAcquire, RwLockWriteAcquire};
+pub use self::semaphore::{Semaphore, SemaphoreAcquire, Permit};
@@ -0,0 +1,107 @@
+//! A multi-producer, multi-consumer, futures-aware, FIFO queue with back
+//! pressure.
+//!
+//! This channel works like `mpsc::channel`, except that `Receiver` can be
+//! cloned. Each message is delivered to exactly one of the receivers, which
+//! makes this channel suitable for distributing work across a number of
+//! tasks.
+//!
+//! The back pressure semantics are the same as for `mpsc::channel`: the
+//! channel capacity is `buffer + num-senders`, so each sender gets one
+//! guaranteed slot, and a sender which fills up the channel is parked until a
+//! receiver takes a message out of it.
+//!
+//! # Disconnection
+//!
+//! When all `Sender` handles have been dropped, receivers will yield the
+//! remaining messages in the channel and then terminate. When all `Receiver`
+//! handles have been dropped, or `Receiver::close` has been called, sending
+//! will fail.
+
+use std::prelude::v1::*;
+
+use std::collections::VecDeque;
+
+use {Async, Poll, StartSend, Sink, Stream};
+use super::bounded;
+
+pub use super::bounded::SendError;
+
+/// The transmission end of a channel which is used to send values.
+///
+/// This is created by the `channel` method.
+pub struct Sender<T> {
+    inner: bounded::Sender<VecDeque<T>>,
+}
+
+/// The receiving end of a channel which implements the `Stream` trait.
+///
+/// Receivers can be cloned, and each message sent on the channel is yielded
+/// by exactly one of them. This is created by the `channel` method.
+pub struct Receiver<T> {
+    inner: bounded::Receiver<VecDeque<T>>,
+}
+
+fn _assert_kinds() {
+    fn _assert_send<T: Send>() {}
+    fn _assert_sync<T: Sync>() {}
+    _assert_send::<Sender<u32>>();
+    _assert_sync::<Sender<u32>>();
+    _assert_send::<Receiver<u32>>();
+    _assert_sync::<Receiver<u32>>();
+}
+
+/// Creates a bounded channel with cloneable receivers, returning the sender
+/// and receiver halves.
+///
+/// The channel capacity is equal to `buffer + num-senders`, as with
+/// `mpsc::channel`. Further senders and receivers are created by cloning the
+/// returned handles.
+pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = bounded::channel(buffer);
+    (Sender { inner: tx }, Receiver { inner: rx })
+}
+
+impl<T> Sink for Sender<T> {
+    type SinkItem = T;
+    type SinkError = SendError<T>;
+
+    fn start_send(&mut self, msg: T) -> StartSend<T, SendError<T>> {
+        self.inner.start_send(msg)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<T>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        Sender { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Closes the channel for all receivers.
+    ///
+    /// This prevents any further messages from being sent on the channel while
+    /// still enabling the receivers to drain messages that are buffered.
+    pub fn close(&mut self) {
+        self.inner.close()
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<T>, ()> {
+        self.inner.poll()
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        Receiver { inner: self.inner.clone() }
+    }
+}
@@ -70,7 +70,7 @@
 use std::any::Any;
 use std::error::Error;
 use std::fmt;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -92,7 +92,7 @@ pub struct Sender<T> {
     // Handle to the task that is blocked on this sender. This handle is sent
     // to the receiver half in order to be notified when the sender becomes
     // unblocked.
-    sender_task: SenderTask,
+    sender_task: Arc<Mutex<SenderTask>>,
 
     // True if the sender might be blocked. This is an optimization to avoid
     // having to lock the mutex most of the time.
@@ -163,6 +163,103 @@ impl<T> SendError<T> {
     }
 }
 
+/// Error type returned by `Sender::try_send`.
+///
+/// The message that failed to be sent is returned along with the error.
+pub enum TrySendError<T> {
+    /// The channel is at capacity and the sender is waiting for the receiver
+    /// to take messages out of it.
+    Full(T),
+    /// The receiving end of the channel has been dropped or closed.
+    Disconnected(T),
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TrySendError::Full(_) => "Full",
+            TrySendError::Disconnected(_) => "Disconnected",
+        };
+        fmt.debug_tuple(name)
+            .field(&"...")
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrySendError::Full(_) => write!(fmt, "send failed because channel is full"),
+            TrySendError::Disconnected(_) => {
+                write!(fmt, "send failed because receiver is gone")
+            }
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T>
+    where T: Any
+{
+    fn description(&self) -> &str {
+        match *self {
+            TrySendError::Full(_) => "send failed because channel is full",
+            TrySendError::Disconnected(_) => "send failed because receiver is gone",
+        }
+    }
+}
+
+impl<T> TrySendError<T> {
+    /// Returns whether the send failed because the channel was full.
+    pub fn is_full(&self) -> bool {
+        match *self {
+            TrySendError::Full(_) => true,
+            TrySendError::Disconnected(_) => false,
+        }
+    }
+
+    /// Returns whether the send failed because the receiver is gone.
+    pub fn is_disconnected(&self) -> bool {
+        !self.is_full()
+    }
+
+    /// Returns the message that was attempted to be sent but failed.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(msg) | TrySendError::Disconnected(msg) => msg,
+        }
+    }
+}
+
+/// Error type returned by `Receiver::try_recv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// There are no messages in the channel right now, but more may be sent.
+    Empty,
+    /// All senders have been dropped or the channel was closed, and there are
+    /// no messages left in it.
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryRecvError::Empty => write!(fmt, "receive failed because channel is empty"),
+            TryRecvError::Disconnected => {
+                write!(fmt, "receive failed because all senders are gone")
+            }
+        }
+    }
+}
+
+impl Error for TryRecvError {
+    fn description(&self) -> &str {
+        match *self {
+            TryRecvError::Empty => "receive failed because channel is empty",
+            TryRecvError::Disconnected => "receive failed because all senders are gone",
+        }
+    }
+}
+
 struct Inner<T> {
     // Max buffer size of the channel. If `None` then the channel is unbounded.
     buffer: Option<usize>,
@@ -175,13 +272,18 @@ struct Inner<T> {
     message_queue: Queue<Option<T>>,
 
     // Atomic, FIFO queue used to send parked task handles to the receiver.
-    parked_queue: Queue<SenderTask>,
+    parked_queue: Queue<Arc<Mutex<SenderTask>>>,
 
     // Number of senders in existence
     num_senders: AtomicUsize,
 
     // Handle to the receiver's task.
     recv_task: Mutex<ReceiverTask>,
+
+    // Set while the `None` message terminating the stream is in the message
+    // queue. It's counted in `state` like any other message, so this lets
+    // `len` leave it out.
+    term_queued: AtomicBool,
 }
 
 // Struct representation of `Inner::state`.
@@ -221,7 +323,27 @@ const MAX_CAPACITY: usize = !(OPEN_MASK);
 const MAX_BUFFER: usize = MAX_CAPACITY >> 1;
 
 // Sent to the consumer to wake up blocked producers
-type SenderTask = Arc<Mutex<Option<Task>>>;
+struct SenderTask {
+    // The task to unpark, which is `None` if the sender was parked outside of
+    // a task, for example by `try_send`
+    task: Option<Task>,
+    is_parked: bool,
+}
+
+impl SenderTask {
+    fn new() -> SenderTask {
+        SenderTask {
+            task: None,
+            is_parked: false,
+        }
+    }
+
+    // Marks the sender as unparked, returning the task to notify
+    fn unpark(&mut self) -> Option<Task> {
+        self.is_parked = false;
+        self.task.take()
+    }
+}
 
 /// Creates an in-memory channel implementation of the `Stream` trait with
 /// bounded capacity.
@@ -271,11 +393,12 @@ fn channel2<T>(buffer: Option<usize>) -> (Sender<T>, Receiver<T>) {
             unparked: false,
             task: None,
         }),
+        term_queued: AtomicBool::new(false),
     });
 
     let tx = Sender {
         inner: inner.clone(),
-        sender_task: Arc::new(Mutex::new(None)),
+        sender_task: Arc::new(Mutex::new(SenderTask::new())),
         maybe_parked: false,
     };
 
@@ -293,6 +416,75 @@ fn channel2<T>(buffer: Option<usize>) -> (Sender<T>, Receiver<T>) {
  */
 
 impl<T> Sender<T> {
+    /// Attempts to send a message on this channel without waiting.
+    ///
+    /// This fails with `TrySendError::Full` if the sender is blocked because
+    /// the channel is at capacity, or with `TrySendError::Disconnected` if the
+    /// receiver has gone away, returning the message in both cases. Like
+    /// `start_send`, a sender may always send one message into a full channel
+    /// through its guaranteed slot, after which it's blocked until the
+    /// receiver takes a message out. This function does not need to be called
+    /// from within a task.
+    pub fn try_send(&mut self, msg: T) -> Result<(), TrySendError<T>> {
+        if !self.poll_unparked(false).is_ready() {
+            return Err(TrySendError::Full(msg))
+        }
+        match self.do_send(Some(msg), false) {
+            Ok(()) => Ok(()),
+            Err(SendError(msg)) => Err(TrySendError::Disconnected(msg)),
+        }
+    }
+
+    /// Polls the channel to determine whether a message can be sent.
+    ///
+    /// This returns `Ready` once the next call to `start_send` or `try_send`
+    /// will accept a message, which is useful to avoid building an expensive
+    /// message only to have it rejected. If the channel is at capacity then
+    /// the current task is notified once there's room. An error is returned
+    /// if the receiver has gone away.
+    ///
+    /// This function must be called from within a task.
+    pub fn poll_ready(&mut self) -> Poll<(), SendError<()>> {
+        let state = decode_state(self.inner.state.load(SeqCst));
+        if !state.is_open {
+            return Err(SendError(()))
+        }
+        Ok(self.poll_unparked(true))
+    }
+
+    /// Returns the number of messages currently buffered in the channel.
+    ///
+    /// As other handles may be sending or receiving concurrently, this should
+    /// only be used as a hint, for example to report the depth of a queue.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are no messages buffered in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of messages the channel can hold before senders
+    /// are blocked.
+    ///
+    /// As described in `channel`, this is the `buffer` the channel was created
+    /// with plus one guaranteed slot for each sender.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the number of `Sender` handles to this channel that are alive.
+    pub fn sender_count(&self) -> usize {
+        self.inner.num_senders.load(SeqCst)
+    }
+
+    /// Returns `true` if the receiver has closed the channel or been dropped,
+    /// in which case no more messages can be sent.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
     // Do the send without failing
     fn do_send(&mut self, msg: Option<T>, can_park: bool) -> Result<(), SendError<T>> {
         // First, increment the number of messages contained by the channel.
@@ -353,6 +545,10 @@ impl<T> Sender<T> {
 
     // Push message to the queue and signal to the receiver
     fn queue_push_and_signal(&self, msg: Option<T>) {
+        if msg.is_none() {
+            self.inner.term_queued.store(true, SeqCst);
+        }
+
         // Push the message onto the message queue
         self.inner.message_queue.push(msg);
 
@@ -442,7 +638,11 @@ impl<T> Sender<T> {
             None
         };
 
-        *self.sender_task.lock().unwrap() = task;
+        {
+            let mut sender_task = self.sender_task.lock().unwrap();
+            sender_task.task = task;
+            sender_task.is_parked = true;
+        }
 
         // Send handle over queue
         let t = self.sender_task.clone();
@@ -454,14 +654,16 @@ impl<T> Sender<T> {
         self.maybe_parked = state.is_open;
     }
 
-    fn poll_unparked(&mut self) -> Async<()> {
+    // Checks whether the sender is still parked. If it is and `do_park` is
+    // set then the current task is notified once it's unparked.
+    fn poll_unparked(&mut self, do_park: bool) -> Async<()> {
         // First check the `maybe_parked` variable. This avoids acquiring the
         // lock in most cases
         if self.maybe_parked {
             // Get a lock on the task handle
-            let mut task = self.sender_task.lock().unwrap();
+            let mut sender_task = self.sender_task.lock().unwrap();
 
-            if task.is_none() {
+            if !sender_task.is_parked {
                 self.maybe_parked = false;
                 return Async::Ready(())
             }
@@ -472,7 +674,9 @@ impl<T> Sender<T> {
             //
             // Update the task in case the `Sender` has been moved to another
             // task
-            *task = Some(task::park());
+            if do_park {
+                sender_task.task = Some(task::park());
+            }
 
             Async::NotReady
         } else {
@@ -488,7 +692,7 @@ impl<T> Sink for Sender<T> {
     fn start_send(&mut self, msg: T) -> StartSend<T, SendError<T>> {
         // If the sender is currently blocked, reject the message before doing
         // any work.
-        if !self.poll_unparked().is_ready() {
+        if !self.poll_unparked(true).is_ready() {
             return Ok(AsyncSink::NotReady(msg));
         }
 
@@ -512,6 +716,28 @@ impl<T> UnboundedSender<T> {
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
         self.0.do_send_nb(msg)
     }
+
+    /// Returns the number of messages currently buffered in the channel.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no messages buffered in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of `UnboundedSender` handles to this channel that
+    /// are alive.
+    pub fn sender_count(&self) -> usize {
+        self.0.sender_count()
+    }
+
+    /// Returns `true` if the receiver has closed the channel or been dropped,
+    /// in which case no more messages can be sent.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
 }
 
 impl<T> Sink for UnboundedSender<T> {
@@ -571,7 +797,7 @@ impl<T> Clone for Sender<T> {
             if actual == curr {
                 return Sender {
                     inner: self.inner.clone(),
-                    sender_task: Arc::new(Mutex::new(None)),
+                    sender_task: Arc::new(Mutex::new(SenderTask::new())),
                     maybe_parked: false,
                 };
             }
@@ -627,7 +853,7 @@ impl<T> Receiver<T> {
         loop {
             match unsafe { self.inner.parked_queue.pop() } {
                 PopResult::Data(task) => {
-                    let task = task.lock().unwrap().take();
+                    let task = task.lock().unwrap().unpark();
                     if let Some(task) = task {
                         task.unpark();
                     }
@@ -638,6 +864,66 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Attempts to receive a message without waiting.
+    ///
+    /// This returns `TryRecvError::Empty` if there is no message available
+    /// right now, and `TryRecvError::Disconnected` if all senders have gone
+    /// away (or the channel was closed) and there are no more buffered
+    /// messages. This function does not need to be called from within a task.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        match self.next_message() {
+            Async::Ready(msg) => {
+                self.unpark_one();
+                self.dec_num_messages(msg.is_none());
+                msg.ok_or(TryRecvError::Disconnected)
+            }
+            Async::NotReady => {
+                let state = decode_state(self.inner.state.load(SeqCst));
+                if !state.is_open && state.num_messages == 0 {
+                    Err(TryRecvError::Disconnected)
+                } else {
+                    Err(TryRecvError::Empty)
+                }
+            }
+        }
+    }
+
+    /// Returns the number of messages currently buffered in the channel.
+    ///
+    /// As other handles may be sending or receiving concurrently, this should
+    /// only be used as a hint, for example to report the depth of a queue.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are no messages buffered in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of messages the channel can hold before senders
+    /// are blocked.
+    ///
+    /// As described in `channel`, this is the `buffer` the channel was created
+    /// with plus one guaranteed slot for each sender.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the number of `Sender` handles to this channel that are alive.
+    pub fn sender_count(&self) -> usize {
+        self.inner.num_senders.load(SeqCst)
+    }
+
+    /// Returns `true` if the channel has been closed, either through `close`
+    /// or because all senders have been dropped.
+    ///
+    /// Note that there may still be buffered messages to receive from a closed
+    /// channel.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
     fn next_message(&mut self) -> Async<Option<T>> {
         // Pop off a message
         loop {
@@ -674,7 +960,7 @@ impl<T> Receiver<T> {
                 PopResult::Data(task) => {
                     // Do this step first so that the lock is dropped when
                     // `unpark` is called
-                    let task = task.lock().unwrap().take();
+                    let task = task.lock().unwrap().unpark();
 
                     if let Some(task) = task {
                         task.unpark();
@@ -717,7 +1003,9 @@ impl<T> Receiver<T> {
         TryPark::Parked
     }
 
-    fn dec_num_messages(&self) {
+    // Decrement the number of queued messages, `term` being whether the
+    // message taken was the `None` terminating the stream
+    fn dec_num_messages(&self, term: bool) {
         let mut curr = self.inner.state.load(SeqCst);
 
         loop {
@@ -731,6 +1019,10 @@ impl<T> Receiver<T> {
                 Err(actual) => curr = actual,
             }
         }
+
+        if term {
+            self.inner.term_queued.store(false, SeqCst);
+        }
     }
 }
 
@@ -773,7 +1065,7 @@ impl<T> Stream for Receiver<T> {
             self.unpark_one();
 
             // Decrement number of messages
-            self.dec_num_messages();
+            self.dec_num_messages(msg.is_none());
 
             // Return the message
             return Ok(Async::Ready(msg));
@@ -799,6 +1091,35 @@ impl<T> UnboundedReceiver<T> {
     pub fn close(&mut self) {
         self.0.close();
     }
+
+    /// Attempts to receive a message without waiting.
+    ///
+    /// See `Receiver::try_recv` for more details.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.0.try_recv()
+    }
+
+    /// Returns the number of messages currently buffered in the channel.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no messages buffered in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of `UnboundedSender` handles to this channel that
+    /// are alive.
+    pub fn sender_count(&self) -> usize {
+        self.0.sender_count()
+    }
+
+    /// Returns `true` if the channel has been closed, either through `close`
+    /// or because all senders have been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
 }
 
 impl<T> Stream for UnboundedReceiver<T> {
@@ -817,6 +1138,24 @@ impl<T> Stream for UnboundedReceiver<T> {
  */
 
 impl<T> Inner<T> {
+    fn len(&self) -> usize {
+        let state = decode_state(self.state.load(SeqCst));
+        if self.term_queued.load(SeqCst) {
+            state.num_messages.saturating_sub(1)
+        } else {
+            state.num_messages
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        let buffer = self.buffer.expect("unbounded channels have no capacity");
+        buffer + self.num_senders.load(SeqCst)
+    }
+
+    fn is_closed(&self) -> bool {
+        !decode_state(self.state.load(SeqCst)).is_open
+    }
+
     // The return value is such that the total number of messages that can be
     // enqueued into the channel will never exceed MAX_CAPACITY
     fn max_senders(&self) -> usize {
@@ -0,0 +1,199 @@
+use std::prelude::v1::*;
+
+use std::cell::{Cell, UnsafeCell};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::{self, Arc};
+
+use {Async, Future, Poll};
+use task::Task;
+use super::wait_queue::WaitQueue;
+
+/// A futures-aware mutex which can be shared between any number of owners.
+///
+/// Unlike `BiLock`, a `Mutex` handle can be cloned freely, and all clones
+/// refer to the same protected value. The lock is acquired through the `lock`
+/// method, which returns a future resolving to a `MutexGuard` once the lock is
+/// held. Tasks waiting for the lock are queued and granted it in the order
+/// that they started waiting.
+///
+/// Guards own a handle to the mutex, so they aren't tied to the lifetime of
+/// the `Mutex` they came from and can be passed along a chain of combinators
+/// such as `and_then`.
+pub struct Mutex<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    state: sync::Mutex<State>,
+    data: UnsafeCell<T>,
+}
+
+struct State {
+    locked: bool,
+
+    // Tasks waiting for the lock in FIFO order. Only the front of the queue
+    // is allowed to take the lock when it's released.
+    waiters: WaitQueue<()>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+fn _assert_kinds() {
+    fn _assert_send<T: Send>() {}
+    fn _assert_sync<T: Sync>() {}
+    _assert_send::<Mutex<Cell<u32>>>();
+    _assert_sync::<Mutex<Cell<u32>>>();
+    _assert_send::<MutexGuard<Cell<u32>>>();
+    _assert_sync::<MutexGuard<u32>>();
+}
+
+impl<T> Mutex<T> {
+    /// Creates a new mutex protecting the provided data.
+    pub fn new(t: T) -> Mutex<T> {
+        Mutex {
+            inner: Arc::new(Inner {
+                state: sync::Mutex::new(State {
+                    locked: false,
+                    waiters: WaitQueue::new(),
+                }),
+                data: UnsafeCell::new(t),
+            }),
+        }
+    }
+
+    /// Returns a future which resolves to a guard once the lock is acquired.
+    ///
+    /// The returned future will never resolve to an error, and if it's
+    /// dropped before completing then it gives up its place in the queue.
+    pub fn lock(&self) -> MutexAcquire<T> {
+        MutexAcquire {
+            inner: self.inner.clone(),
+            id: None,
+        }
+    }
+
+    /// Attempts to acquire the lock without waiting.
+    ///
+    /// This returns `None` if the lock is currently held or if there are other
+    /// tasks already waiting for it. This function does not need to be called
+    /// from within a task.
+    pub fn try_lock(&self) -> Option<MutexGuard<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.locked || !state.waiters.is_empty() {
+            return None
+        }
+        state.locked = true;
+        Some(MutexGuard { inner: self.inner.clone(), _marker: PhantomData })
+    }
+}
+
+impl<T> Clone for Mutex<T> {
+    fn clone(&self) -> Mutex<T> {
+        Mutex { inner: self.inner.clone() }
+    }
+}
+
+impl State {
+    // Returns the task at the front of the queue if the lock is free, which
+    // should be unparked (after releasing the state lock) so it can take the
+    // lock the next time it's polled.
+    fn wake_front(&mut self) -> Option<Task> {
+        let locked = self.locked;
+        self.waiters.wake_front(|_| !locked)
+    }
+}
+
+/// Future returned by `Mutex::lock` which will resolve when the lock is
+/// acquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct MutexAcquire<T> {
+    inner: Arc<Inner<T>>,
+    // Our id in the queue of waiters, if we've been queued
+    id: Option<usize>,
+}
+
+impl<T> Future for MutexAcquire<T> {
+    type Item = MutexGuard<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<MutexGuard<T>, ()> {
+        let mut state = self.inner.state.lock().unwrap();
+        let state = &mut *state;
+        let locked = &mut state.locked;
+        let ret = state.waiters.poll_acquire(&mut self.id, (), || {
+            !mem::replace(locked, true)
+        });
+        match ret {
+            Async::Ready(()) => Ok(Async::Ready(MutexGuard { inner: self.inner.clone(), _marker: PhantomData })),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl<T> Drop for MutexAcquire<T> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            // Leave the queue, and if we were next in line then pass on the
+            // wakeup to whoever is now at the front.
+            let task = {
+                let mut state = self.inner.state.lock().unwrap();
+                state.waiters.remove(id);
+                state.wake_front()
+            };
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+}
+
+/// An RAII guard for a locked `Mutex`, resolved from `MutexAcquire`.
+///
+/// This structure acts as a sentinel to the data in the `Mutex<T>` itself,
+/// implementing `Deref` and `DerefMut` to `T`. When dropped, the lock will be
+/// unlocked and the next task waiting for it woken up.
+pub struct MutexGuard<T> {
+    inner: Arc<Inner<T>>,
+    // Shared references to the guard hand out `&T`, so it may only be `Sync`
+    // if `T` is, unlike the mutex itself.
+    _marker: PhantomData<*const T>,
+}
+
+unsafe impl<T: Send> Send for MutexGuard<T> {}
+unsafe impl<T: Send + Sync> Sync for MutexGuard<T> {}
+
+impl<T> MutexGuard<T> {
+    /// Returns a handle to the mutex this guard has locked.
+    pub fn mutex(&self) -> Mutex<T> {
+        Mutex { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Deref for MutexGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.inner.data.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.inner.data.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<T> {
+    fn drop(&mut self) {
+        let task = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.locked = false;
+            state.wake_front()
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+}
@@ -175,6 +175,16 @@ impl<T> Sender<T> {
             Ok(Async::NotReady)
         }
     }
+
+    /// Tests to see whether this `Sender`'s corresponding `Receiver` has gone
+    /// away.
+    ///
+    /// Unlike `poll_cancel`, this function does not need to be called from
+    /// within a task and never arranges for a notification. If `true` is
+    /// returned then a value sent with `complete` will never be received.
+    pub fn is_canceled(&self) -> bool {
+        self.inner.complete.load(SeqCst)
+    }
 }
 
 impl<T> Drop for Sender<T> {
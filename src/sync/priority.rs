@@ -0,0 +1,178 @@
+//! A multi-producer, single-consumer, futures-aware priority queue with back
+//! pressure.
+//!
+//! This channel works like `mpsc::channel`, except that each message is sent
+//! along with a priority. The `Receiver` always yields the message with the
+//! highest priority out of those in the channel, and messages with equal
+//! priorities are yielded in the order they were sent.
+//!
+//! The back pressure semantics are the same as for `mpsc::channel`: the
+//! channel capacity is `buffer + num-senders`, so each sender gets one
+//! guaranteed slot, and a sender which fills up the channel is parked until the
+//! receiver takes a message out of it.
+//!
+//! # Disconnection
+//!
+//! When all `Sender` handles have been dropped, the receiver will yield the
+//! remaining messages in the channel and then terminate. If the receiver is
+//! dropped then sending will fail.
+//!
+//! # Clean Shutdown
+//!
+//! As with `mpsc`, the receiver can call `close` to prevent any further
+//! messages from being sent, and then drain the messages still in the channel
+//! before being dropped.
+
+use std::prelude::v1::*;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use {Async, Poll, StartSend, Sink, Stream};
+use super::bounded::{self, Queue};
+
+pub use super::bounded::SendError;
+
+/// The transmission end of a channel which is used to send values along with
+/// their priority.
+///
+/// This is created by the `channel` method.
+pub struct Sender<P: Ord, T> {
+    inner: bounded::Sender<PriorityQueue<P, T>>,
+}
+
+/// The receiving end of a channel which implements the `Stream` trait,
+/// yielding the message with the highest priority first.
+///
+/// This is created by the `channel` method.
+pub struct Receiver<P: Ord, T> {
+    inner: bounded::Receiver<PriorityQueue<P, T>>,
+}
+
+fn _assert_kinds() {
+    fn _assert_send<T: Send>() {}
+    fn _assert_sync<T: Sync>() {}
+    _assert_send::<Sender<u8, u32>>();
+    _assert_sync::<Sender<u8, u32>>();
+    _assert_send::<Receiver<u8, u32>>();
+}
+
+struct PriorityQueue<P, T> {
+    messages: BinaryHeap<Entry<P, T>>,
+
+    // Sequence number of the next message sent, which keeps messages of the
+    // same priority in FIFO order
+    next_seq: u64,
+}
+
+struct Entry<P, T> {
+    priority: P,
+    seq: u64,
+    msg: T,
+}
+
+/// Creates a bounded priority channel, returning the sender and receiver
+/// halves.
+///
+/// Messages are sent as `(priority, message)` pairs, and the receiver yields
+/// the messages with the greatest priority first. The channel capacity is
+/// equal to `buffer + num-senders`, as with `mpsc::channel`.
+pub fn channel<P: Ord, T>(buffer: usize) -> (Sender<P, T>, Receiver<P, T>) {
+    let (tx, rx) = bounded::channel(buffer);
+    (Sender { inner: tx }, Receiver { inner: rx })
+}
+
+impl<P: Ord, T> Default for PriorityQueue<P, T> {
+    fn default() -> PriorityQueue<P, T> {
+        PriorityQueue {
+            messages: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<P: Ord, T> Queue for PriorityQueue<P, T> {
+    type Item = (P, T);
+    type Output = T;
+
+    fn push(&mut self, (priority, msg): (P, T)) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.messages.push(Entry {
+            priority: priority,
+            seq: seq,
+            msg: msg,
+        });
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.messages.pop().map(|entry| entry.msg)
+    }
+
+    fn len(&self) -> usize {
+        self.messages.len()
+    }
+}
+
+impl<P: Ord, T> Sink for Sender<P, T> {
+    type SinkItem = (P, T);
+    type SinkError = SendError<(P, T)>;
+
+    fn start_send(&mut self, item: (P, T)) -> StartSend<(P, T), SendError<(P, T)>> {
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<(P, T)>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<P: Ord, T> Clone for Sender<P, T> {
+    fn clone(&self) -> Sender<P, T> {
+        Sender { inner: self.inner.clone() }
+    }
+}
+
+impl<P: Ord, T> Receiver<P, T> {
+    /// Closes the receiving half
+    ///
+    /// This prevents any further messages from being sent on the channel while
+    /// still enabling the receiver to drain messages that are buffered.
+    pub fn close(&mut self) {
+        self.inner.close()
+    }
+}
+
+impl<P: Ord, T> Stream for Receiver<P, T> {
+    type Item = T;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<T>, ()> {
+        self.inner.poll()
+    }
+}
+
+impl<P: Ord, T> Ord for Entry<P, T> {
+    fn cmp(&self, other: &Entry<P, T>) -> Ordering {
+        // The heap yields its greatest entry first, so an earlier sequence
+        // number sorts as greater within a priority.
+        match self.priority.cmp(&other.priority) {
+            Ordering::Equal => other.seq.cmp(&self.seq),
+            o => o,
+        }
+    }
+}
+
+impl<P: Ord, T> PartialOrd for Entry<P, T> {
+    fn partial_cmp(&self, other: &Entry<P, T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Ord, T> PartialEq for Entry<P, T> {
+    fn eq(&self, other: &Entry<P, T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<P: Ord, T> Eq for Entry<P, T> {}
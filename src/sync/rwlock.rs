@@ -0,0 +1,289 @@
+use std::prelude::v1::*;
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{self, Arc};
+
+use {Async, Future, Poll};
+use task::Task;
+use super::wait_queue::WaitQueue;
+
+/// A futures-aware reader-writer lock which can be shared between any number
+/// of owners.
+///
+/// This lock allows any number of readers or at most one writer at a time.
+/// Locks are acquired through the `read` and `write` methods, which return
+/// futures resolving to guards. Tasks waiting for the lock are queued and
+/// granted it in the order that they started waiting, with consecutive
+/// readers at the front of the queue being let in together.
+///
+/// Like `Mutex`, handles can be cloned freely and guards own a handle to the
+/// lock, so they can be passed along a chain of combinators.
+pub struct RwLock<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    state: sync::Mutex<State>,
+    data: UnsafeCell<T>,
+}
+
+struct State {
+    held: Held,
+
+    // Tasks waiting for the lock in FIFO order, along with whether they want
+    // to write. See `Mutex` for more details.
+    waiters: WaitQueue<bool>,
+}
+
+// Who currently holds the lock
+struct Held {
+    readers: usize,
+    writer: bool,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send + Sync> Sync for Inner<T> {}
+
+impl<T> RwLock<T> {
+    /// Creates a new reader-writer lock protecting the provided data.
+    pub fn new(t: T) -> RwLock<T> {
+        RwLock {
+            inner: Arc::new(Inner {
+                state: sync::Mutex::new(State {
+                    held: Held { readers: 0, writer: false },
+                    waiters: WaitQueue::new(),
+                }),
+                data: UnsafeCell::new(t),
+            }),
+        }
+    }
+
+    /// Returns a future which resolves to a guard once shared read access is
+    /// acquired.
+    ///
+    /// The returned future will never resolve to an error.
+    pub fn read(&self) -> RwLockReadAcquire<T> {
+        RwLockReadAcquire {
+            inner: Acquire { inner: self.inner.clone(), id: None, write: false },
+        }
+    }
+
+    /// Returns a future which resolves to a guard once exclusive write access
+    /// is acquired.
+    ///
+    /// The returned future will never resolve to an error.
+    pub fn write(&self) -> RwLockWriteAcquire<T> {
+        RwLockWriteAcquire {
+            inner: Acquire { inner: self.inner.clone(), id: None, write: true },
+        }
+    }
+
+    /// Attempts to acquire shared read access without waiting.
+    ///
+    /// This returns `None` if a writer holds the lock or if there are other
+    /// tasks already waiting for it.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if !state.waiters.is_empty() || !state.held.try_acquire(false) {
+            return None
+        }
+        Some(RwLockReadGuard { inner: self.inner.clone() })
+    }
+
+    /// Attempts to acquire exclusive write access without waiting.
+    ///
+    /// This returns `None` if the lock is held or if there are other tasks
+    /// already waiting for it.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if !state.waiters.is_empty() || !state.held.try_acquire(true) {
+            return None
+        }
+        Some(RwLockWriteGuard { inner: self.inner.clone() })
+    }
+}
+
+impl<T> Clone for RwLock<T> {
+    fn clone(&self) -> RwLock<T> {
+        RwLock { inner: self.inner.clone() }
+    }
+}
+
+impl Held {
+    fn try_acquire(&mut self, write: bool) -> bool {
+        if self.writer || (write && self.readers > 0) {
+            return false
+        }
+        if write {
+            self.writer = true;
+        } else {
+            self.readers += 1;
+        }
+        true
+    }
+}
+
+impl State {
+    // Returns the task at the front of the queue if it could acquire the lock
+    // in its current state, which should be unparked after releasing the state
+    // lock.
+    fn wake_front(&mut self) -> Option<Task> {
+        let held = &self.held;
+        self.waiters.wake_front(|&write| !(held.writer || (write && held.readers > 0)))
+    }
+}
+
+// Shared implementation of the read and write acquisition futures
+struct Acquire<T> {
+    inner: Arc<Inner<T>>,
+    id: Option<usize>,
+    write: bool,
+}
+
+impl<T> Acquire<T> {
+    fn poll(&mut self) -> Async<()> {
+        let (ret, task) = self.poll_locked();
+        if let Some(task) = task {
+            task.unpark();
+        }
+        ret
+    }
+
+    fn poll_locked(&mut self) -> (Async<()>, Option<Task>) {
+        let mut state = self.inner.state.lock().unwrap();
+        let write = self.write;
+        let ret = {
+            let state = &mut *state;
+            let held = &mut state.held;
+            state.waiters.poll_acquire(&mut self.id, write, || held.try_acquire(write))
+        };
+        match ret {
+            // If we're a reader then the next waiter may be one too, in which
+            // case it can be let in alongside us.
+            Async::Ready(()) => (Async::Ready(()), state.wake_front()),
+            Async::NotReady => (Async::NotReady, None),
+        }
+    }
+}
+
+impl<T> Drop for Acquire<T> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            let task = {
+                let mut state = self.inner.state.lock().unwrap();
+                state.waiters.remove(id);
+                state.wake_front()
+            };
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+}
+
+/// Future returned by `RwLock::read` which will resolve when shared read
+/// access is acquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct RwLockReadAcquire<T> {
+    inner: Acquire<T>,
+}
+
+impl<T> Future for RwLockReadAcquire<T> {
+    type Item = RwLockReadGuard<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<RwLockReadGuard<T>, ()> {
+        match self.inner.poll() {
+            Async::Ready(()) => {
+                Ok(Async::Ready(RwLockReadGuard { inner: self.inner.inner.clone() }))
+            }
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// Future returned by `RwLock::write` which will resolve when exclusive write
+/// access is acquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct RwLockWriteAcquire<T> {
+    inner: Acquire<T>,
+}
+
+impl<T> Future for RwLockWriteAcquire<T> {
+    type Item = RwLockWriteGuard<T>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<RwLockWriteGuard<T>, ()> {
+        match self.inner.poll() {
+            Async::Ready(()) => {
+                Ok(Async::Ready(RwLockWriteGuard { inner: self.inner.inner.clone() }))
+            }
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// An RAII guard for shared read access to a `RwLock`, resolved from
+/// `RwLockReadAcquire`.
+///
+/// This implements `Deref` to the protected data, and releases its access
+/// when dropped.
+pub struct RwLockReadGuard<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Deref for RwLockReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.inner.data.get() }
+    }
+}
+
+impl<T> Drop for RwLockReadGuard<T> {
+    fn drop(&mut self) {
+        let task = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.held.readers -= 1;
+            state.wake_front()
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+}
+
+/// An RAII guard for exclusive write access to a `RwLock`, resolved from
+/// `RwLockWriteAcquire`.
+///
+/// This implements `Deref` and `DerefMut` to the protected data, and releases
+/// the lock when dropped.
+pub struct RwLockWriteGuard<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Deref for RwLockWriteGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.inner.data.get() }
+    }
+}
+
+impl<T> DerefMut for RwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.inner.data.get() }
+    }
+}
+
+impl<T> Drop for RwLockWriteGuard<T> {
+    fn drop(&mut self) {
+        let task = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.held.writer = false;
+            state.wake_front()
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+}
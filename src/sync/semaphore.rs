@@ -0,0 +1,210 @@
+use std::prelude::v1::*;
+
+use std::sync::{self, Arc};
+
+use {Async, Future, Poll};
+use task::Task;
+use super::wait_queue::WaitQueue;
+
+/// A futures-aware counting semaphore.
+///
+/// A semaphore hands out a limited number of permits, which can be used to
+/// bound the number of concurrent operations across any number of tasks.
+/// Permits are acquired through the `acquire` method, which returns a future
+/// resolving to a `Permit` once enough permits are available, and they're
+/// returned to the semaphore when the `Permit` is dropped.
+///
+/// Tasks waiting for permits are served in the order that they started
+/// waiting, so a large request won't be starved by a stream of small ones.
+/// Handles to a semaphore can be cloned freely, and all clones share the same
+/// permits.
+pub struct Semaphore {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    state: sync::Mutex<State>,
+}
+
+struct State {
+    permits: usize,
+
+    // Tasks waiting for permits in FIFO order, along with how many permits
+    // they want. Only the front of the queue may take permits that are
+    // released.
+    waiters: WaitQueue<usize>,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with `permits` permits available.
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            inner: Arc::new(Inner {
+                state: sync::Mutex::new(State {
+                    permits: permits,
+                    waiters: WaitQueue::new(),
+                }),
+            }),
+        }
+    }
+
+    /// Returns a future which resolves to a `Permit` for `permits` permits
+    /// once they're available.
+    ///
+    /// The returned future will never resolve to an error, and if it's dropped
+    /// before completing then it gives up its place in the queue. Note that if
+    /// more permits are requested than the semaphore will ever have then the
+    /// future will never complete, and will hold up all waiters behind it.
+    pub fn acquire(&self, permits: usize) -> SemaphoreAcquire {
+        SemaphoreAcquire {
+            inner: self.inner.clone(),
+            permits: permits,
+            id: None,
+        }
+    }
+
+    /// Attempts to acquire `permits` permits without waiting.
+    ///
+    /// This returns `None` if there aren't enough permits available or if
+    /// there are other tasks already waiting for permits. This function does
+    /// not need to be called from within a task.
+    pub fn try_acquire(&self, permits: usize) -> Option<Permit> {
+        let mut state = self.inner.state.lock().unwrap();
+        if !state.waiters.is_empty() || state.permits < permits {
+            return None
+        }
+        state.permits -= permits;
+        Some(Permit { inner: self.inner.clone(), permits: permits })
+    }
+
+    /// Returns the number of permits which are currently available.
+    pub fn available_permits(&self) -> usize {
+        self.inner.state.lock().unwrap().permits
+    }
+
+    /// Adds `permits` new permits to the semaphore, waking up waiting tasks
+    /// if they can now proceed.
+    pub fn add_permits(&self, permits: usize) {
+        self.inner.release(permits);
+    }
+}
+
+impl Clone for Semaphore {
+    fn clone(&self) -> Semaphore {
+        Semaphore { inner: self.inner.clone() }
+    }
+}
+
+impl Inner {
+    fn release(&self, permits: usize) {
+        let task = {
+            let mut state = self.state.lock().unwrap();
+            state.permits += permits;
+            state.wake_front()
+        };
+        if let Some(task) = task {
+            task.unpark();
+        }
+    }
+}
+
+impl State {
+    // Returns the task at the front of the queue if there are now enough
+    // permits for it, which should be unparked after releasing the state lock.
+    fn wake_front(&mut self) -> Option<Task> {
+        let permits = self.permits;
+        self.waiters.wake_front(|&wanted| wanted <= permits)
+    }
+}
+
+/// Future returned by `Semaphore::acquire` which will resolve when the
+/// requested permits are acquired.
+#[must_use = "futures do nothing unless polled"]
+pub struct SemaphoreAcquire {
+    inner: Arc<Inner>,
+    permits: usize,
+    // Our id in the queue of waiters, if we've been queued
+    id: Option<usize>,
+}
+
+impl SemaphoreAcquire {
+    fn poll_locked(&mut self) -> (Async<()>, Option<Task>) {
+        let mut state = self.inner.state.lock().unwrap();
+        let wanted = self.permits;
+        let ret = {
+            let state = &mut *state;
+            let permits = &mut state.permits;
+            state.waiters.poll_acquire(&mut self.id, wanted, || {
+                if *permits < wanted {
+                    return false
+                }
+                *permits -= wanted;
+                true
+            })
+        };
+        match ret {
+            // There may be enough permits left over for the next waiter too
+            Async::Ready(()) => (Async::Ready(()), state.wake_front()),
+            Async::NotReady => (Async::NotReady, None),
+        }
+    }
+}
+
+impl Future for SemaphoreAcquire {
+    type Item = Permit;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Permit, ()> {
+        let (ret, task) = self.poll_locked();
+        if let Some(task) = task {
+            task.unpark();
+        }
+        match ret {
+            Async::Ready(()) => {
+                Ok(Async::Ready(Permit {
+                    inner: self.inner.clone(),
+                    permits: self.permits,
+                }))
+            }
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl Drop for SemaphoreAcquire {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            // Leave the queue, and if we were holding up the waiters behind us
+            // then one of them may be able to proceed now.
+            let task = {
+                let mut state = self.inner.state.lock().unwrap();
+                state.waiters.remove(id);
+                state.wake_front()
+            };
+            if let Some(task) = task {
+                task.unpark();
+            }
+        }
+    }
+}
+
+/// A set of permits acquired from a `Semaphore`.
+///
+/// The permits are returned to the semaphore when this is dropped.
+pub struct Permit {
+    inner: Arc<Inner>,
+    permits: usize,
+}
+
+impl Permit {
+    /// Returns the number of permits held.
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.inner.release(self.permits);
+    }
+}
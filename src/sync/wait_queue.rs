@@ -0,0 +1,102 @@
+use std::prelude::v1::*;
+
+use std::collections::VecDeque;
+
+use Async;
+use task::{self, Task};
+
+/// A FIFO queue of tasks waiting to acquire a shared resource, used by
+/// `Mutex`, `RwLock` and `Semaphore`.
+///
+/// Each waiter is identified by an id handed out when it's queued, and
+/// carries a `W` describing what it wants to acquire. Only the waiter at the
+/// front of the queue is allowed to acquire the resource, which is what keeps
+/// these primitives fair. The queue is always accessed under the primitive's
+/// state lock, and any task it returns should be unparked after releasing
+/// that lock.
+pub struct WaitQueue<W> {
+    waiters: VecDeque<Waiter<W>>,
+    next_id: usize,
+}
+
+struct Waiter<W> {
+    id: usize,
+    want: W,
+    task: Option<Task>,
+}
+
+impl<W> WaitQueue<W> {
+    pub fn new() -> WaitQueue<W> {
+        WaitQueue {
+            waiters: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+
+    /// Attempts to acquire the resource on behalf of the waiter whose id is
+    /// stored in `id`, or of a new waiter if `id` is `None`.
+    ///
+    /// `acquire` is only called when it's the waiter's turn, that is when
+    /// it's at the front of the queue or when it isn't queued yet and nobody
+    /// else is waiting. If it returns `true` then the waiter leaves the queue
+    /// and `Ready` is returned. Otherwise the current task is parked, the
+    /// waiter is queued if it wasn't already, and `id` is updated to match.
+    pub fn poll_acquire<F>(&mut self, id: &mut Option<usize>, want: W, acquire: F)
+                           -> Async<()>
+        where F: FnOnce() -> bool
+    {
+        match *id {
+            None => {
+                if self.waiters.is_empty() && acquire() {
+                    return Async::Ready(())
+                }
+                let new_id = self.next_id;
+                self.next_id = self.next_id.wrapping_add(1);
+                self.waiters.push_back(Waiter {
+                    id: new_id,
+                    want: want,
+                    task: Some(task::park()),
+                });
+                *id = Some(new_id);
+            }
+            Some(me) => {
+                let front = self.waiters.front().map(|w| w.id) == Some(me);
+                if front && acquire() {
+                    self.waiters.pop_front();
+                    *id = None;
+                    return Async::Ready(())
+                }
+                // Update the task in case we've been moved to another one
+                for waiter in self.waiters.iter_mut() {
+                    if waiter.id == me {
+                        waiter.task = Some(task::park());
+                    }
+                }
+            }
+        }
+        Async::NotReady
+    }
+
+    /// Removes the waiter with the given id, when it gives up waiting.
+    pub fn remove(&mut self, id: usize) {
+        self.waiters.retain(|w| w.id != id);
+    }
+
+    /// Returns the task of the waiter at the front of the queue if `ready`
+    /// says it could now acquire what it wants.
+    ///
+    /// The task is only handed out once per wakeup, until the waiter polls
+    /// again.
+    pub fn wake_front<F>(&mut self, ready: F) -> Option<Task>
+        where F: FnOnce(&W) -> bool
+    {
+        match self.waiters.front_mut() {
+            Some(ref mut waiter) if ready(&waiter.want) => waiter.task.take(),
+            _ => None,
+        }
+    }
+}
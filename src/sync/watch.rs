@@ -0,0 +1,293 @@
+//! A single-producer, multi-consumer channel which only retains the most
+//! recently sent value.
+//!
+//! A watch channel is useful for broadcasting the latest state of something,
+//! such as a configuration, to any number of interested tasks. Every time the
+//! `Sender` updates the value all receivers are notified, but receivers which
+//! fall behind only ever observe the latest value rather than every
+//! intermediate one.
+//!
+//! The current value can be read at any time, even outside of a task, with
+//! `Receiver::borrow`.
+//!
+//! # Disconnection
+//!
+//! When the `Sender` is dropped, receivers will yield the latest value if they
+//! haven't seen it yet and then terminate. If all receivers have been dropped
+//! then sending will fail.
+
+use std::prelude::v1::*;
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::atomic::Ordering::SeqCst;
+
+use task::{self, Task};
+use {Async, AsyncSink, Poll, StartSend, Sink, Stream};
+
+/// The sending half of a watch channel, used to update the value.
+///
+/// This is created by the `channel` function.
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The receiving half of a watch channel, which implements `Stream`.
+///
+/// The stream yields a copy of the value every time it changes, skipping any
+/// values which were overwritten before the receiver got to them. Cloning a
+/// receiver creates a new receiver which has seen the same value as the
+/// original. This is created by the `channel` function.
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+    id: usize,
+    // The version of the value this receiver has last yielded
+    version: usize,
+}
+
+/// A reference to the current value of a watch channel.
+///
+/// This is created by `Receiver::borrow` and holds a read lock on the value,
+/// so the `Sender` will block when updating it while this is alive.
+pub struct Ref<'a, T: 'a> {
+    inner: RwLockReadGuard<'a, T>,
+}
+
+fn _assert_kinds() {
+    fn _assert_send<T: Send>() {}
+    fn _assert_sync<T: Sync>() {}
+    _assert_send::<Sender<u32>>();
+    _assert_sync::<Sender<u32>>();
+    _assert_send::<Receiver<u32>>();
+}
+
+/// Error type for sending, used when all receivers have been dropped.
+///
+/// The value that failed to be sent is returned along with the error.
+pub struct SendError<T>(T);
+
+struct Inner<T> {
+    value: RwLock<T>,
+
+    // Incremented every time the value is updated, always while holding the
+    // write lock on `value`.
+    version: AtomicUsize,
+
+    // Set once the sender has gone away
+    closed: AtomicBool,
+
+    num_receivers: AtomicUsize,
+
+    // Receivers waiting for the value to change, keyed by receiver id.
+    //
+    // A receiver stores its task here and then checks `version` and `closed`
+    // again, while the sender updates those before taking the tasks out, so
+    // an update can never be missed.
+    rx_tasks: Mutex<HashMap<usize, Task>>,
+    next_id: AtomicUsize,
+}
+
+/// Creates a new watch channel holding the value `init`, returning the sender
+/// and receiver halves.
+///
+/// The returned receiver considers `init` as already seen, so it will first
+/// yield a value once it's been updated through the `Sender`. Further receivers
+/// can be created by cloning the receiver.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{Future, Stream};
+/// use futures::sync::watch;
+///
+/// let (tx, rx) = watch::channel("hello");
+/// assert_eq!(*rx.borrow(), "hello");
+///
+/// tx.send("world").unwrap();
+/// drop(tx);
+/// assert_eq!(rx.collect().wait(), Ok(vec!["world"]));
+/// ```
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        value: RwLock::new(init),
+        version: AtomicUsize::new(0),
+        closed: AtomicBool::new(false),
+        num_receivers: AtomicUsize::new(1),
+        rx_tasks: Mutex::new(HashMap::new()),
+        next_id: AtomicUsize::new(1),
+    });
+    let receiver = Receiver {
+        inner: inner.clone(),
+        id: 0,
+        version: 0,
+    };
+    let sender = Sender {
+        inner: inner,
+    };
+    (sender, receiver)
+}
+
+impl<T> Sender<T> {
+    /// Replaces the value in the channel, notifying all receivers.
+    ///
+    /// An error is returned, along with the value, if all receivers have been
+    /// dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.inner.num_receivers.load(SeqCst) == 0 {
+            return Err(SendError(value))
+        }
+        {
+            let mut slot = self.inner.value.write().unwrap();
+            *slot = value;
+            self.inner.version.fetch_add(1, SeqCst);
+        }
+        self.inner.notify();
+        Ok(())
+    }
+}
+
+impl<T> Sink for Sender<T> {
+    type SinkItem = T;
+    type SinkError = SendError<T>;
+
+    fn start_send(&mut self, value: T) -> StartSend<T, SendError<T>> {
+        try!(Sender::send(self, value));
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<T>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<'a, T> Sink for &'a Sender<T> {
+    type SinkItem = T;
+    type SinkError = SendError<T>;
+
+    fn start_send(&mut self, value: T) -> StartSend<T, SendError<T>> {
+        try!(Sender::send(self, value));
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), SendError<T>> {
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.inner.closed.store(true, SeqCst);
+        self.inner.notify();
+    }
+}
+
+impl<T> Inner<T> {
+    fn notify(&self) {
+        let tasks = self.rx_tasks.lock().unwrap()
+            .drain()
+            .map(|(_, task)| task)
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.unpark();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns a reference to the most recently sent value.
+    ///
+    /// This can be called outside of a task, and doesn't mark the value as
+    /// seen by this receiver.
+    pub fn borrow(&self) -> Ref<T> {
+        Ref { inner: self.inner.value.read().unwrap() }
+    }
+}
+
+impl<T: Clone> Stream for Receiver<T> {
+    type Item = T;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<T>, ()> {
+        if self.inner.version.load(SeqCst) == self.version {
+            if self.inner.closed.load(SeqCst) {
+                return Ok(Async::Ready(None))
+            }
+
+            // Store our task and then check again to see whether the sender
+            // changed anything in the meantime, see the comment on `rx_tasks`.
+            self.inner.rx_tasks.lock().unwrap().insert(self.id, task::park());
+            if self.inner.version.load(SeqCst) == self.version {
+                if self.inner.closed.load(SeqCst) {
+                    return Ok(Async::Ready(None))
+                }
+                return Ok(Async::NotReady)
+            }
+        }
+
+        // The version is read while holding the lock on the value so the two
+        // are guaranteed to match.
+        let value = self.inner.value.read().unwrap();
+        self.version = self.inner.version.load(SeqCst);
+        Ok(Async::Ready(Some(value.clone())))
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.inner.num_receivers.fetch_add(1, SeqCst);
+        Receiver {
+            inner: self.inner.clone(),
+            id: self.inner.next_id.fetch_add(1, SeqCst),
+            version: self.version,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.num_receivers.fetch_sub(1, SeqCst);
+        self.inner.rx_tasks.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl<'a, T> Deref for Ref<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("SendError")
+            .field(&"...")
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "send failed because all receivers are gone")
+    }
+}
+
+impl<T> Error for SendError<T>
+    where T: Any
+{
+    fn description(&self) -> &str {
+        "send failed because all receivers are gone"
+    }
+}
+
+impl<T> SendError<T> {
+    /// Returns the value that was attempted to be sent but failed.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
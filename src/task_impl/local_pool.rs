@@ -0,0 +1,239 @@
+use std::prelude::v1::*;
+
+use std::cell::RefCell;
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use {Future, Async};
+use stack::Stack;
+use super::{Spawn, spawn, Unpark, ThreadUnpark};
+
+/// A single-threaded executor which multiplexes many tasks on the thread that
+/// owns it.
+///
+/// Unlike executors which hand work off to other threads, a `LocalPool` runs
+/// all of its tasks on the thread calling `run` or `run_until`, and as such
+/// the futures spawned onto it are not required to be `Send`. Tasks are only
+/// polled when they've been unparked, so a large number of idle tasks costs
+/// nothing while the pool is running.
+///
+/// New tasks can be spawned with `spawn_local`, either directly on the pool or
+/// from within a running task through a `LocalSpawner` handle.
+pub struct LocalPool {
+    // A slab of the tasks that have been spawned onto this pool. Each slot is
+    // either a running task or a pointer to the next empty slot, and the index
+    // of a slot is the identifier pushed onto `notify.ready` when the task is
+    // unparked.
+    tasks: Vec<Slot>,
+    next_task: usize,
+    active: usize,
+
+    // Tasks which have been spawned but not yet moved into `tasks`. These are
+    // kept separately as they may be pushed to while a task is being polled.
+    incoming: Rc<RefCell<Vec<LocalTask>>>,
+
+    notify: Arc<Notify>,
+}
+
+/// A handle to a `LocalPool` used to spawn new tasks onto it.
+///
+/// Spawners are created with `LocalPool::spawner` and can be cloned and moved
+/// into the tasks running on the pool, allowing tasks to spawn further work.
+#[derive(Clone)]
+pub struct LocalSpawner {
+    incoming: Weak<RefCell<Vec<LocalTask>>>,
+    notify: Arc<Notify>,
+}
+
+type LocalTask = Spawn<Box<Future<Item = (), Error = ()>>>;
+
+enum Slot {
+    Next(usize),
+    Task(LocalTask, Arc<Unpark>),
+}
+
+// State shared with all of the `Unpark` handles given out by the pool. Any
+// unpark enqueues the relevant task and wakes up the thread running the pool
+// if it's blocked.
+struct Notify {
+    ready: Stack<usize>,
+    main: AtomicBool,
+    thread: ThreadUnpark,
+}
+
+struct TaskUnpark {
+    id: usize,
+    notify: Arc<Notify>,
+}
+
+struct MainUnpark(Arc<Notify>);
+
+impl LocalPool {
+    /// Creates a new, empty pool which will run its tasks on the current
+    /// thread.
+    pub fn new() -> LocalPool {
+        LocalPool {
+            tasks: Vec::new(),
+            next_task: 0,
+            active: 0,
+            incoming: Rc::new(RefCell::new(Vec::new())),
+            notify: Arc::new(Notify {
+                ready: Stack::new(),
+                main: AtomicBool::new(false),
+                thread: ThreadUnpark::new(thread::current()),
+            }),
+        }
+    }
+
+    /// Returns a handle which can be used to spawn tasks onto this pool.
+    pub fn spawner(&self) -> LocalSpawner {
+        LocalSpawner {
+            incoming: Rc::downgrade(&self.incoming),
+            notify: self.notify.clone(),
+        }
+    }
+
+    /// Spawns a new task onto this pool.
+    ///
+    /// The future will not be polled until the pool is run with `run` or
+    /// `run_until`. Note that the future is not required to be `Send`.
+    pub fn spawn_local<F>(&self, future: F)
+        where F: Future<Item = (), Error = ()> + 'static,
+    {
+        self.incoming.borrow_mut().push(spawn(Box::new(future)));
+    }
+
+    /// Runs all tasks in this pool until they've all completed.
+    ///
+    /// This function will block the current thread while there are no tasks
+    /// ready to make progress.
+    pub fn run(&mut self) {
+        loop {
+            self.poll_pool();
+            if self.active == 0 && self.incoming.borrow().is_empty() {
+                return
+            }
+            self.notify.thread.park();
+        }
+    }
+
+    /// Runs all tasks in this pool, along with `future`, until `future`
+    /// completes.
+    ///
+    /// The result of `future` is returned as soon as it's available, and any
+    /// tasks which are still running at that point are left in the pool to be
+    /// run later. Unlike spawned tasks the given future may borrow from the
+    /// surrounding stack frame.
+    pub fn run_until<F>(&mut self, future: F) -> Result<F::Item, F::Error>
+        where F: Future,
+    {
+        let mut main = spawn(future);
+        let unpark: Arc<Unpark> = Arc::new(MainUnpark(self.notify.clone()));
+        self.notify.main.store(true, Ordering::SeqCst);
+        loop {
+            if self.notify.main.swap(false, Ordering::SeqCst) {
+                match main.poll_future(unpark.clone()) {
+                    Ok(Async::NotReady) => {}
+                    Ok(Async::Ready(e)) => return Ok(e),
+                    Err(e) => return Err(e),
+                }
+            }
+            self.poll_pool();
+            if !self.notify.main.load(Ordering::SeqCst) {
+                self.notify.thread.park();
+            }
+        }
+    }
+
+    // Moves all incoming tasks into the pool and then polls every task which
+    // has been unparked, looping until no more tasks are immediately ready.
+    fn poll_pool(&mut self) {
+        loop {
+            let incoming = mem::replace(&mut *self.incoming.borrow_mut(),
+                                        Vec::new());
+            for task in incoming {
+                self.insert(task);
+            }
+
+            // The stack hands back notifications in LIFO order, so reverse
+            // them to poll tasks in the order that they were unparked.
+            let ready = self.notify.ready.drain().collect::<Vec<_>>();
+            if ready.is_empty() {
+                return
+            }
+            for id in ready.into_iter().rev() {
+                let done = match self.tasks[id] {
+                    Slot::Task(ref mut task, ref unpark) => {
+                        match task.poll_future(unpark.clone()) {
+                            Ok(Async::NotReady) => false,
+                            Ok(Async::Ready(())) | Err(()) => true,
+                        }
+                    }
+                    // Stale notification for a task that already finished
+                    Slot::Next(_) => false,
+                };
+                if done {
+                    self.active -= 1;
+                    self.tasks[id] = Slot::Next(self.next_task);
+                    self.next_task = id;
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, task: LocalTask) {
+        let id = self.next_task;
+        if id == self.tasks.len() {
+            self.tasks.push(Slot::Next(id + 1));
+        }
+        let unpark = Arc::new(TaskUnpark {
+            id: id,
+            notify: self.notify.clone(),
+        });
+        match mem::replace(&mut self.tasks[id], Slot::Task(task, unpark)) {
+            Slot::Next(next) => self.next_task = next,
+            Slot::Task(..) => panic!(),
+        }
+        self.active += 1;
+        self.notify.ready.push(id);
+    }
+}
+
+impl Default for LocalPool {
+    fn default() -> LocalPool {
+        LocalPool::new()
+    }
+}
+
+impl LocalSpawner {
+    /// Spawns a new task onto the pool this handle was created from.
+    ///
+    /// If the pool is currently running then it will be woken up to poll the
+    /// new task. If the pool has since been dropped then the future is
+    /// dropped immediately.
+    pub fn spawn_local<F>(&self, future: F)
+        where F: Future<Item = (), Error = ()> + 'static,
+    {
+        if let Some(incoming) = self.incoming.upgrade() {
+            incoming.borrow_mut().push(spawn(Box::new(future)));
+            self.notify.thread.unpark();
+        }
+    }
+}
+
+impl Unpark for TaskUnpark {
+    fn unpark(&self) {
+        self.notify.ready.push(self.id);
+        self.notify.thread.unpark();
+    }
+}
+
+impl Unpark for MainUnpark {
+    fn unpark(&self) {
+        self.0.main.store(true, Ordering::SeqCst);
+        self.0.thread.unpark();
+    }
+}
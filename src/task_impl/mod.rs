@@ -15,10 +15,12 @@ use self::unpark_mutex::UnparkMutex;
 
 mod task_rc;
 mod data;
+mod local_pool;
 #[allow(deprecated)]
 #[cfg(feature = "with-deprecated")]
 pub use self::task_rc::TaskRc;
 pub use self::data::LocalKey;
+pub use self::local_pool::{LocalPool, LocalSpawner};
 
 thread_local!(static CURRENT_TASK: Cell<(*const Task, *const data::LocalMap)> = {
     Cell::new((0 as *const _, 0 as *const _))
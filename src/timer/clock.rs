@@ -0,0 +1,62 @@
+use std::prelude::v1::*;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time for a `Timer`.
+///
+/// Timers consult their clock whenever they need to know what time it is,
+/// which allows tests to swap in a `MockClock` that only moves forward when
+/// told to.
+pub trait Clock: Send + Sync + 'static {
+    /// Returns the current time according to this clock.
+    fn now(&self) -> Instant;
+}
+
+/// A `Clock` which reads the current time from the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A `Clock` whose time only changes when it's explicitly advanced.
+///
+/// All clones of a `MockClock` share the same time, so one handle can be given
+/// to a `Timer` while another is kept around to move time forward.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    /// Creates a new clock frozen at the current system time.
+    pub fn new() -> MockClock {
+        MockClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Moves this clock forward by `dur`.
+    ///
+    /// Note that this won't by itself wake up any tasks waiting on timers
+    /// using this clock, `Timer::turn` needs to be called to fire them.
+    pub fn advance(&self, dur: Duration) {
+        *self.now.lock().unwrap() += dur;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> MockClock {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
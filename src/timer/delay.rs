@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use {Future, Poll, Async};
+use super::Timer;
+
+/// A future which completes at a specific instant in time.
+///
+/// This is created by the `Timer::delay` and `Timer::delay_until` methods.
+#[must_use = "futures do nothing unless polled"]
+pub struct Delay {
+    timer: Timer,
+    token: usize,
+    at: Instant,
+}
+
+pub fn new(timer: Timer, at: Instant) -> Delay {
+    Delay {
+        token: timer.insert(at),
+        timer: timer,
+        at: at,
+    }
+}
+
+impl Delay {
+    /// Returns the instant at which this future completes.
+    pub fn deadline(&self) -> Instant {
+        self.at
+    }
+
+    /// Resets this future to instead complete at `at`.
+    ///
+    /// This can be called whether or not the future has already completed,
+    /// and the future may be polled again afterwards.
+    pub fn reset(&mut self, at: Instant) {
+        self.at = at;
+        self.timer.reset(self.token, at);
+    }
+
+    /// Returns the timer this future was created from.
+    pub fn timer(&self) -> &Timer {
+        &self.timer
+    }
+}
+
+impl Future for Delay {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        // Check the clock first so a delay completes as soon as its deadline
+        // has passed, even if the wheel hasn't been advanced yet.
+        if self.at <= self.timer.now() || self.timer.poll(self.token) {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        self.timer.remove(self.token);
+    }
+}
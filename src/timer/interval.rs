@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use {Future, Poll, Async};
+use stream::Stream;
+use super::Delay;
+
+/// A stream which yields `()` at a fixed rate.
+///
+/// Each item is scheduled relative to the deadline of the previous one rather
+/// than to when it was observed, so an interval doesn't drift if its consumer
+/// is slow. This is created by the `Timer::interval` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct Interval {
+    delay: Delay,
+    dur: Duration,
+}
+
+pub fn new(delay: Delay, dur: Duration) -> Interval {
+    Interval {
+        delay: delay,
+        dur: dur,
+    }
+}
+
+impl Stream for Interval {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<()>, ()> {
+        try_ready!(self.delay.poll());
+        let next = self.delay.deadline() + self.dur;
+        self.delay.reset(next);
+        Ok(Async::Ready(Some(())))
+    }
+}
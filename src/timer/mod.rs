@@ -0,0 +1,281 @@
+//! Timers for futures and streams
+//!
+//! This module contains a `Timer`, which tracks any number of timeouts without
+//! requiring a thread per timeout, along with the `Delay` future and the
+//! `Interval` stream which are created from it. Timeouts can also be applied to
+//! arbitrary futures and streams through `Future::timeout` and
+//! `Stream::timeout`, which fail with `TimeoutError::Elapsed` once their
+//! deadline passes.
+//!
+//! Internally timers are kept in a hashed timer wheel which is advanced either
+//! by a background thread (for timers created with `Timer::new`) or manually
+//! through `Timer::turn`. The clock used by a timer is pluggable, and a
+//! `MockClock` is provided so tests can advance time by hand.
+
+use std::prelude::v1::*;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Weak, Mutex, Once, ONCE_INIT};
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod clock;
+mod delay;
+mod interval;
+mod wheel;
+pub use self::clock::{Clock, SystemClock, MockClock};
+pub use self::delay::Delay;
+pub use self::interval::Interval;
+use self::wheel::Wheel;
+
+/// A handle to a timer wheel which fires `Delay`s and `Interval`s.
+///
+/// Timers are cheaply cloneable handles, and all clones refer to the same
+/// underlying wheel. Most code can simply use `Timer::default()` (which is
+/// what `Future::timeout` and friends use), while tests can create a timer
+/// with a custom clock through `Timer::with_clock`.
+#[derive(Clone)]
+pub struct Timer {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    clock: Box<Clock>,
+    wheel: Mutex<Wheel>,
+    driver: Mutex<Option<Driver>>,
+}
+
+// The background thread advancing a wheel, if any, along with the time it's
+// going to wake up at next.
+struct Driver {
+    thread: thread::Thread,
+    wakeup: Option<Instant>,
+}
+
+/// Error returned by the futures and streams created by `Future::timeout` and
+/// `Stream::timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutError<E> {
+    /// The deadline elapsed before the underlying future or stream produced a
+    /// value.
+    Elapsed,
+    /// The underlying future or stream produced an error.
+    Inner(E),
+}
+
+impl Timer {
+    /// Creates a new timer using the system clock.
+    ///
+    /// This will spawn one background thread which sleeps until the next timer
+    /// is due to fire. The thread exits once all handles to the timer and all
+    /// delays created from it have been dropped.
+    pub fn new() -> Timer {
+        let timer = Timer::with_clock(SystemClock);
+        let weak = Arc::downgrade(&timer.inner);
+        let thread = thread::Builder::new()
+            .name("futures-timer".to_string())
+            .spawn(move || run(weak))
+            .expect("failed to spawn timer thread");
+        *timer.inner.driver.lock().unwrap() = Some(Driver {
+            thread: thread.thread().clone(),
+            wakeup: None,
+        });
+        timer
+    }
+
+    /// Creates a new timer which reads the time from `clock`.
+    ///
+    /// No background thread is spawned for the returned timer, so it's only
+    /// advanced when `turn` is called. Note that `Delay`s and `Interval`s
+    /// always consult the clock when they're polled, so they will complete
+    /// even if the timer isn't turned, but tasks waiting on them will only be
+    /// unparked by a call to `turn`.
+    pub fn with_clock<C: Clock>(clock: C) -> Timer {
+        let start = clock.now();
+        Timer {
+            inner: Arc::new(Inner {
+                clock: Box::new(clock),
+                wheel: Mutex::new(Wheel::new(start)),
+                driver: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Returns the current time according to this timer's clock.
+    pub fn now(&self) -> Instant {
+        self.inner.clock.now()
+    }
+
+    /// Fires all timers which have expired according to this timer's clock,
+    /// unparking the tasks waiting on them.
+    pub fn turn(&self) {
+        let now = self.now();
+        let tasks = self.inner.wheel.lock().unwrap().advance(now);
+        for task in tasks {
+            task.unpark();
+        }
+    }
+
+    /// Creates a future which completes once `dur` has elapsed.
+    pub fn delay(&self, dur: Duration) -> Delay {
+        self.delay_until(self.now() + dur)
+    }
+
+    /// Creates a future which completes at the instant `at`.
+    pub fn delay_until(&self, at: Instant) -> Delay {
+        delay::new(self.clone(), at)
+    }
+
+    /// Creates a stream which yields an item every `dur`, starting `dur` from
+    /// now.
+    pub fn interval(&self, dur: Duration) -> Interval {
+        interval::new(self.delay(dur), dur)
+    }
+
+    fn insert(&self, at: Instant) -> usize {
+        let token = self.inner.wheel.lock().unwrap().insert(at);
+        self.inner.wake_driver(at);
+        token
+    }
+
+    fn reset(&self, token: usize, at: Instant) {
+        self.inner.wheel.lock().unwrap().reset(token, at);
+        self.inner.wake_driver(at);
+    }
+
+    fn remove(&self, token: usize) {
+        self.inner.wheel.lock().unwrap().remove(token);
+    }
+
+    fn poll(&self, token: usize) -> bool {
+        self.inner.wheel.lock().unwrap().poll(token)
+    }
+}
+
+impl Default for Timer {
+    /// Returns a handle to the default timer.
+    ///
+    /// This is the timer most recently configured on this thread through
+    /// `with_default`, or otherwise a global timer using the system clock
+    /// which is lazily created the first time it's needed.
+    fn default() -> Timer {
+        if let Some(timer) = CURRENT.with(|c| c.borrow().clone()) {
+            return timer
+        }
+
+        static INIT: Once = ONCE_INIT;
+        static mut GLOBAL: *const Timer = 0 as *const Timer;
+        unsafe {
+            INIT.call_once(|| {
+                GLOBAL = Box::into_raw(Box::new(Timer::new()));
+            });
+            (*GLOBAL).clone()
+        }
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Timer").finish()
+    }
+}
+
+thread_local!(static CURRENT: RefCell<Option<Timer>> = RefCell::new(None));
+
+/// Sets `timer` as the default timer for the current thread for the duration
+/// of the closure `f`.
+///
+/// Any timers implicitly created while `f` is running, such as those created
+/// by `Future::timeout` and `Stream::timeout`, will use `timer` instead of the
+/// global system timer. This is primarily useful for testing with a
+/// `MockClock`.
+pub fn with_default<F, R>(timer: &Timer, f: F) -> R
+    where F: FnOnce() -> R
+{
+    struct Reset(Option<Timer>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            CURRENT.with(|c| *c.borrow_mut() = prev);
+        }
+    }
+
+    let prev = CURRENT.with(|c| mem::replace(&mut *c.borrow_mut(), Some(timer.clone())));
+    let _reset = Reset(prev);
+    f()
+}
+
+impl Inner {
+    // Wakes up the background thread if a timer was inserted which fires
+    // before the thread was otherwise going to wake up.
+    fn wake_driver(&self, at: Instant) {
+        let mut driver = self.driver.lock().unwrap();
+        if let Some(ref mut driver) = *driver {
+            if driver.wakeup.map(|w| at < w).unwrap_or(true) {
+                driver.wakeup = Some(at);
+                driver.thread.unpark();
+            }
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Some(ref driver) = *self.driver.lock().unwrap() {
+            driver.thread.unpark();
+        }
+    }
+}
+
+fn run(inner: Weak<Inner>) {
+    loop {
+        let wakeup = {
+            let inner = match inner.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+            let timer = Timer { inner: inner };
+            timer.turn();
+
+            // Hold the driver lock while looking at the wheel so any timer
+            // inserted concurrently either shows up here or sees our new
+            // wakeup time and unparks us.
+            let mut driver = timer.inner.driver.lock().unwrap();
+            let wakeup = timer.inner.wheel.lock().unwrap().next_wakeup();
+            if let Some(ref mut driver) = *driver {
+                driver.wakeup = wakeup;
+            }
+            wakeup
+        };
+        match wakeup {
+            Some(at) => {
+                let now = Instant::now();
+                if at > now {
+                    thread::park_timeout(at - now);
+                }
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for TimeoutError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TimeoutError::Elapsed => write!(fmt, "deadline has elapsed"),
+            TimeoutError::Inner(ref e) => e.fmt(fmt),
+        }
+    }
+}
+
+impl<E: Error> Error for TimeoutError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            TimeoutError::Elapsed => "deadline has elapsed",
+            TimeoutError::Inner(_) => "underlying future or stream failed",
+        }
+    }
+}
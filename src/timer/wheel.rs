@@ -0,0 +1,188 @@
+//! A hashed timer wheel.
+//!
+//! Time is divided into ticks of `TICK_MS` milliseconds, and each timer is
+//! hashed into one of `NUM_SLOTS` slots based on the tick it expires at.
+//! Advancing the wheel only needs to look at the slots for the ticks which
+//! have passed, and timers further out than one rotation simply stay in their
+//! slot until their tick comes around.
+
+use std::prelude::v1::*;
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+use task::{self, Task};
+
+const NUM_SLOTS: usize = 512;
+const TICK_MS: u64 = 1;
+
+pub struct Wheel {
+    // The instant corresponding to tick 0
+    start: Instant,
+
+    // All ticks up to and including this one have been processed, so any
+    // timer expiring at or before `elapsed` has already fired.
+    elapsed: u64,
+
+    // Tokens of the timers expiring in each slot, hashed by expiration tick
+    slots: Vec<Vec<usize>>,
+
+    // A slab of timer states indexed by token, with vacant entries linking to
+    // the next vacant entry.
+    entries: Vec<Entry>,
+    next_entry: usize,
+}
+
+enum Entry {
+    Next(usize),
+    Timer(State),
+}
+
+struct State {
+    when: u64,
+    fired: bool,
+    task: Option<Task>,
+}
+
+impl Wheel {
+    pub fn new(start: Instant) -> Wheel {
+        Wheel {
+            start: start,
+            elapsed: 0,
+            slots: (0..NUM_SLOTS).map(|_| Vec::new()).collect(),
+            entries: Vec::new(),
+            next_entry: 0,
+        }
+    }
+
+    /// Registers a new timer to fire at `at`, returning its token.
+    pub fn insert(&mut self, at: Instant) -> usize {
+        let token = self.next_entry;
+        if token == self.entries.len() {
+            self.entries.push(Entry::Next(token + 1));
+        }
+        let state = State { when: 0, fired: false, task: None };
+        match mem::replace(&mut self.entries[token], Entry::Timer(state)) {
+            Entry::Next(next) => self.next_entry = next,
+            Entry::Timer(_) => panic!(),
+        }
+        self.schedule(token, at);
+        token
+    }
+
+    /// Changes the time that the timer identified by `token` fires at, also
+    /// resetting it if it has already fired.
+    pub fn reset(&mut self, token: usize, at: Instant) {
+        self.unschedule(token);
+        self.schedule(token, at);
+    }
+
+    /// Deregisters the timer identified by `token`.
+    pub fn remove(&mut self, token: usize) {
+        self.unschedule(token);
+        self.entries[token] = Entry::Next(self.next_entry);
+        self.next_entry = token;
+    }
+
+    /// Returns whether the timer identified by `token` has fired, and if it
+    /// hasn't arranges for the current task to be unparked when it does.
+    pub fn poll(&mut self, token: usize) -> bool {
+        let state = self.state(token);
+        if !state.fired {
+            state.task = Some(task::park());
+        }
+        state.fired
+    }
+
+    /// Fires all timers which expire at or before `now`, returning the tasks
+    /// which should be unparked as a result.
+    pub fn advance(&mut self, now: Instant) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        let now = self.ticks(now, false);
+        if now <= self.elapsed {
+            return tasks
+        }
+
+        // If more than a full rotation has passed then every slot needs to be
+        // looked at, but there's no need to look at any of them twice.
+        let ticks = now - self.elapsed;
+        let ticks = if ticks > NUM_SLOTS as u64 {NUM_SLOTS as u64} else {ticks};
+        for tick in self.elapsed + 1..self.elapsed + 1 + ticks {
+            let slot = (tick % NUM_SLOTS as u64) as usize;
+            let mut i = 0;
+            while i < self.slots[slot].len() {
+                let token = self.slots[slot][i];
+                let state = match self.entries[token] {
+                    Entry::Timer(ref mut state) => state,
+                    Entry::Next(_) => panic!(),
+                };
+                if state.when > now {
+                    i += 1;
+                    continue
+                }
+                state.fired = true;
+                tasks.extend(state.task.take());
+                self.slots[slot].swap_remove(i);
+            }
+        }
+        self.elapsed = now;
+        tasks
+    }
+
+    /// Returns a lower bound on the time at which the next timer fires, or
+    /// `None` if there are no pending timers.
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        (self.elapsed + 1..self.elapsed + 1 + NUM_SLOTS as u64).find(|tick| {
+            !self.slots[(tick % NUM_SLOTS as u64) as usize].is_empty()
+        }).map(|tick| self.start + Duration::from_millis(tick * TICK_MS))
+    }
+
+    fn schedule(&mut self, token: usize, at: Instant) {
+        let when = self.ticks(at, true);
+        let fired = when <= self.elapsed;
+        {
+            let state = self.state(token);
+            state.when = when;
+            state.fired = fired;
+        }
+        if !fired {
+            self.slots[(when % NUM_SLOTS as u64) as usize].push(token);
+        }
+    }
+
+    fn unschedule(&mut self, token: usize) {
+        let (when, fired) = {
+            let state = self.state(token);
+            (state.when, state.fired)
+        };
+        if fired {
+            return
+        }
+        let slot = &mut self.slots[(when % NUM_SLOTS as u64) as usize];
+        let pos = slot.iter().position(|t| *t == token).unwrap();
+        slot.swap_remove(pos);
+    }
+
+    fn state(&mut self, token: usize) -> &mut State {
+        match self.entries[token] {
+            Entry::Timer(ref mut state) => state,
+            Entry::Next(_) => panic!("invalid timer token"),
+        }
+    }
+
+    // Converts an instant into a tick count relative to `start`, rounding up
+    // for expiration times so timers never fire early.
+    fn ticks(&self, at: Instant, round_up: bool) -> u64 {
+        if at <= self.start {
+            return 0
+        }
+        let dur = at - self.start;
+        let nanos = dur.as_secs() * 1_000_000_000 + dur.subsec_nanos() as u64;
+        let per_tick = TICK_MS * 1_000_000;
+        if round_up {
+            (nanos + per_tick - 1) / per_tick
+        } else {
+            nanos / per_tick
+        }
+    }
+}
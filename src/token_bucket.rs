@@ -0,0 +1,85 @@
+//! A token bucket shared by the `rate_limit` adaptors of streams and sinks.
+//!
+//! Rather than counting tokens this tracks the "theoretical arrival time" of
+//! the next token (the generic cell rate algorithm), which needs no periodic
+//! refilling: an item may pass if the bucket would have refilled enough by
+//! now, and each item that passes pushes that time back by one interval.
+
+use std::time::{Duration, Instant};
+
+use {Async, Future};
+use timer::{Delay, Timer};
+
+pub struct TokenBucket {
+    timer: Timer,
+    // Time it takes for one token to be added to the bucket
+    interval: Duration,
+    // How far `tat` may be ahead of the current time, `burst - 1` intervals
+    tolerance: Duration,
+    tat: Instant,
+    delay: Option<Delay>,
+}
+
+impl TokenBucket {
+    pub fn new(n: usize, per: Duration) -> TokenBucket {
+        assert!(n > 0, "rate limit must allow at least one item");
+        let timer = Timer::default();
+        let interval = from_nanos(as_nanos(per) / n as u64);
+        TokenBucket {
+            tat: timer.now(),
+            timer: timer,
+            interval: interval,
+            tolerance: intervals(interval, n),
+            delay: None,
+        }
+    }
+
+    pub fn set_burst(&mut self, burst: usize) {
+        assert!(burst > 0, "burst must allow at least one item");
+        self.tolerance = intervals(self.interval, burst);
+    }
+
+    /// Checks whether a token is available without taking it, arranging for
+    /// the current task to be unparked once one is if not.
+    pub fn poll_ready(&mut self) -> Async<()> {
+        let now = self.timer.now();
+        if self.tat <= now + self.tolerance {
+            return Async::Ready(())
+        }
+        let at = self.tat - self.tolerance;
+        match self.delay {
+            Some(ref mut delay) if delay.deadline() != at => delay.reset(at),
+            Some(_) => {}
+            None => self.delay = Some(self.timer.delay_until(at)),
+        }
+        match self.delay.as_mut().unwrap().poll() {
+            Ok(Async::NotReady) => Async::NotReady,
+            Ok(Async::Ready(())) | Err(()) => Async::Ready(()),
+        }
+    }
+
+    /// Takes a token from the bucket, which should be called after
+    /// `poll_ready` returned `Ready`.
+    pub fn take(&mut self) {
+        let now = self.timer.now();
+        let start = if self.tat > now { self.tat } else { now };
+        self.tat = start + self.interval;
+    }
+}
+
+// The tolerance for letting through a burst of `burst` items, that is
+// `burst - 1` intervals. This is done in nanoseconds as `Duration` can only be
+// multiplied by a `u32`, which `burst` may not fit in.
+fn intervals(interval: Duration, burst: usize) -> Duration {
+    from_nanos(as_nanos(interval).saturating_mul(burst as u64 - 1))
+}
+
+fn as_nanos(dur: Duration) -> u64 {
+    dur.as_secs()
+        .saturating_mul(1_000_000_000)
+        .saturating_add(dur.subsec_nanos() as u64)
+}
+
+fn from_nanos(nanos: u64) -> Duration {
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
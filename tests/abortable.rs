@@ -0,0 +1,75 @@
+extern crate futures;
+
+use std::thread;
+use std::time::Duration;
+
+use futures::{Async, Future};
+use futures::executor;
+use futures::future::{self, AbortError, Abortable, AbortHandle};
+use futures::stream;
+use futures::sync::{mpsc, oneshot};
+
+mod support;
+use support::*;
+
+#[test]
+fn completes_normally() {
+    let (f, handle) = future::abortable(future::ok::<u32, u32>(1));
+    assert_eq!(f.wait(), Ok(1));
+    assert!(!handle.is_aborted());
+
+    let (f, _handle) = future::abortable(future::err::<u32, u32>(2));
+    assert_eq!(f.wait(), Err(AbortError::Inner(2)));
+}
+
+#[test]
+fn abort_wakes_task() {
+    let (_tx, rx) = oneshot::channel::<u32>();
+    let (f, handle) = future::abortable(rx);
+    let mut f = executor::spawn(f);
+    let unpark = unpark_counter();
+    assert!(f.poll_future(unpark.clone()).unwrap().is_not_ready());
+
+    handle.abort();
+    assert_eq!(unpark.count(), 1);
+    assert!(handle.is_aborted());
+    assert_eq!(f.poll_future(unpark_noop()), Err(AbortError::Aborted));
+}
+
+#[test]
+fn abort_from_another_thread() {
+    let (_tx, rx) = oneshot::channel::<u32>();
+    let (f, handle) = future::abortable(rx);
+    let t = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        handle.clone().abort();
+    });
+    assert_eq!(f.wait(), Err(AbortError::Aborted));
+    t.join().unwrap();
+}
+
+#[test]
+fn abort_before_first_poll() {
+    let (handle, reg) = AbortHandle::new_pair();
+    handle.abort();
+    let mut f = Abortable::new(future::ok::<u32, ()>(1), reg);
+    // The underlying future isn't polled once aborted
+    assert_eq!(f.poll(), Err(AbortError::Aborted));
+    assert_eq!(f.into_inner().wait(), Ok(1));
+}
+
+#[test]
+fn abort_stream() {
+    let (tx, rx) = mpsc::unbounded::<u32>();
+    let (s, handle) = stream::abortable(rx);
+    let mut s = executor::spawn(s);
+    tx.send(1).unwrap();
+    assert_eq!(s.poll_stream(unpark_noop()), Ok(Async::Ready(Some(1))));
+
+    let unpark = unpark_counter();
+    assert!(s.poll_stream(unpark.clone()).unwrap().is_not_ready());
+    handle.abort();
+    assert_eq!(unpark.count(), 1);
+    tx.send(2).unwrap();
+    assert_eq!(s.poll_stream(unpark_noop()), Err(AbortError::Aborted));
+}
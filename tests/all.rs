@@ -42,6 +42,8 @@ fn result_smoke() {
     assert_done(|| f_err(1).map(|a| a + 2), r_err(1));
     assert_done(|| f_ok(1).map_err(|a| a + 2), r_ok(1));
     assert_done(|| f_err(1).map_err(|a| a + 2), r_err(3));
+    assert_done(|| f_ok(1).inspect(|a| assert_eq!(*a, 1)), r_ok(1));
+    assert_done(|| f_err(1).inspect(|_| panic!()), r_err(1));
     assert_done(|| f_ok(1).and_then(|a| Ok(a + 2)), r_ok(3));
     assert_done(|| f_err(1).and_then(|a| Ok(a + 2)), r_err(1));
     assert_done(|| f_ok(1).and_then(|a| Err(a as u32 + 3)), r_err(4));
@@ -247,10 +249,65 @@ fn collect_collects() {
     assert_done(|| join_all(vec![f_ok(1), f_ok(2)]), Ok(vec![1, 2]));
     assert_done(|| join_all(vec![f_ok(1)]), Ok(vec![1]));
     assert_done(|| join_all(Vec::<Result<i32, u32>>::new()), Ok(vec![]));
+    assert_done(|| join_all(vec![f_ok(1), f_err(2), f_ok(3)]), Err(2));
 
     // TODO: needs more tests
 }
 
+#[test]
+fn join_all_keeps_order() {
+    let (txs, rxs): (Vec<_>, Vec<_>) = (0..3).map(|_| oneshot::channel::<i32>()).unzip();
+    let mut f = executor::spawn(join_all(rxs));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    let mut txs = txs.into_iter().map(Some).collect::<Vec<_>>();
+    for &i in [2, 0, 1].iter() {
+        txs[i].take().unwrap().complete(i as i32);
+    }
+    assert_eq!(f.wait_future(), Ok(vec![0, 1, 2]));
+}
+
+#[test]
+fn join_all_only_polls_woken() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let polls = Rc::new(Cell::new(0));
+    let (txs, rxs): (Vec<_>, Vec<_>) = (0..10).map(|_| oneshot::channel::<i32>()).unzip();
+    let futures = rxs.into_iter().map(|rx| {
+        let polls = polls.clone();
+        let mut rx = rx;
+        future::poll_fn(move || {
+            polls.set(polls.get() + 1);
+            rx.poll()
+        })
+    });
+    let mut f = executor::spawn(join_all(futures.collect::<Vec<_>>()));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(polls.get(), 10);
+
+    let mut txs = txs.into_iter();
+    txs.next().unwrap().complete(1);
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(polls.get(), 11);
+}
+
+#[test]
+fn join_all_settled_collects_errors() {
+    assert_done(|| join_all_settled(vec![f_ok(1), f_err(2), f_ok(3)]),
+                Ok(vec![Ok(1), Err(2), Ok(3)]));
+    assert_done(|| join_all_settled(Vec::<Result<i32, u32>>::new()), Ok(vec![]));
+
+    let (tx, rx) = oneshot::channel::<i32>();
+    let mut f = executor::spawn(join_all_settled(vec![
+        rx.boxed(),
+        future::err(Canceled).boxed(),
+    ]));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    tx.complete(5);
+    assert_eq!(f.wait_future(), Ok(vec![Ok(5), Err(Canceled)]));
+}
+
 #[test]
 fn select2() {
     fn d<T, U, E>(r: Result<(T, U), (E, U)>) -> Result<T, E> {
@@ -0,0 +1,134 @@
+#![cfg(feature = "use_std")]
+
+extern crate futures;
+
+use std::thread;
+
+use futures::{Future, Stream, Async};
+use futures::executor;
+use futures::sync::broadcast::{self, RecvError};
+
+mod support;
+use support::*;
+
+fn is_send<T: Send>() {}
+
+#[test]
+fn bounds() {
+    is_send::<broadcast::Sender<i32>>();
+    is_send::<broadcast::Receiver<i32>>();
+}
+
+#[test]
+fn every_receiver_gets_every_message() {
+    let (tx, rx1) = broadcast::channel::<i32>(16);
+    let rx2 = tx.subscribe();
+
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    drop(tx);
+
+    assert_eq!(rx1.collect().wait(), Ok(vec![1, 2]));
+    assert_eq!(rx2.collect().wait(), Ok(vec![1, 2]));
+}
+
+#[test]
+fn subscribe_starts_at_tail() {
+    let (tx, rx1) = broadcast::channel::<i32>(16);
+    tx.send(1).unwrap();
+    let rx2 = tx.subscribe();
+    tx.send(2).unwrap();
+    drop(tx);
+
+    assert_eq!(rx1.collect().wait(), Ok(vec![1, 2]));
+    assert_eq!(rx2.collect().wait(), Ok(vec![2]));
+}
+
+#[test]
+fn lagged_receiver() {
+    let (tx, rx) = broadcast::channel::<i32>(2);
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+
+    let mut rx = rx.wait();
+    assert_eq!(rx.next(), Some(Err(RecvError::Lagged(3))));
+    assert_eq!(rx.next(), Some(Ok(3)));
+    assert_eq!(rx.next(), Some(Ok(4)));
+    assert_eq!(rx.next(), None);
+}
+
+#[test]
+fn send_without_receivers() {
+    let (tx, rx) = broadcast::channel::<i32>(2);
+    drop(rx);
+    assert_eq!(tx.send(1).unwrap_err().into_inner(), 1);
+
+    let rx = tx.subscribe();
+    tx.send(2).unwrap();
+    drop(tx);
+    assert_eq!(rx.collect().wait(), Ok(vec![2]));
+}
+
+#[test]
+fn clone_receiver_keeps_position() {
+    let (tx, rx1) = broadcast::channel::<i32>(4);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+
+    let mut rx1 = executor::spawn(rx1);
+    assert_eq!(rx1.wait_stream(), Some(Ok(1)));
+    let rx2 = rx1.get_ref().clone();
+    drop(tx);
+
+    assert_eq!(rx1.into_inner().collect().wait(), Ok(vec![2]));
+    assert_eq!(rx2.collect().wait(), Ok(vec![2]));
+}
+
+#[test]
+fn send_unparks_receivers() {
+    let (tx, rx) = broadcast::channel::<i32>(4);
+    let mut rx1 = executor::spawn(rx);
+    let mut rx2 = executor::spawn(tx.subscribe());
+
+    let unpark1 = unpark_counter();
+    let unpark2 = unpark_counter();
+    assert_eq!(rx1.poll_stream(unpark1.clone()).unwrap(), Async::NotReady);
+    assert_eq!(rx2.poll_stream(unpark2.clone()).unwrap(), Async::NotReady);
+
+    tx.send(1).unwrap();
+    assert_eq!(unpark1.count(), 1);
+    assert_eq!(unpark2.count(), 1);
+    assert_eq!(rx1.poll_stream(unpark1.clone()).unwrap(), Async::Ready(Some(1)));
+    assert_eq!(rx1.poll_stream(unpark1.clone()).unwrap(), Async::NotReady);
+
+    drop(tx);
+    assert_eq!(unpark1.count(), 2);
+    assert_eq!(rx1.poll_stream(unpark1.clone()).unwrap(), Async::Ready(None));
+    assert_eq!(rx2.poll_stream(unpark2.clone()).unwrap(), Async::Ready(Some(1)));
+    assert_eq!(rx2.poll_stream(unpark2.clone()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn threaded_fan_out() {
+    use futures::Sink;
+
+    const N: i32 = 1000;
+    let (tx, rx) = broadcast::channel::<i32>(N as usize);
+    let receivers = (0..4).map(|_| tx.subscribe()).collect::<Vec<_>>();
+    drop(rx);
+
+    let threads = receivers.into_iter().map(|rx| {
+        thread::spawn(move || rx.fold(0, |a, b| Ok::<_, RecvError>(a + b)).wait())
+    }).collect::<Vec<_>>();
+
+    // Drive the sender as a `Sink` through a shared reference
+    let (_, rest) = (&tx).send_all(futures::stream::iter((0..N).map(Ok))).wait().ok().unwrap();
+    assert!(rest.wait().next().is_none());
+    drop(tx);
+
+    for t in threads {
+        assert_eq!(t.join().unwrap(), Ok((0..N).sum()));
+    }
+}
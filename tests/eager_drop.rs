@@ -4,6 +4,7 @@ use std::sync::mpsc::channel;
 
 use futures::Poll;
 use futures::future::*;
+use futures::stream::{self, Stream};
 use futures::sync::oneshot;
 
 mod support;
@@ -64,6 +65,41 @@ fn and_then_drops_eagerly() {
     rx2.recv().unwrap();
 }
 
+struct StreamData<S, T> {
+    _data: T,
+    stream: S,
+}
+
+impl<S: Stream, T> Stream for StreamData<S, T> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.stream.poll()
+    }
+}
+
+#[test]
+fn any_all_drop_stream_eagerly() {
+    // The stream should have been dropped by the time the answer is known,
+    // even though it has more items.
+    let (tx, rx) = channel::<()>();
+    let s = StreamData { _data: tx, stream: stream::iter((0..).map(Ok::<i32, ()>)) };
+    let res = s.any(|i| i == 1).map(move |b| {
+        assert!(rx.recv().is_err());
+        b
+    }).wait();
+    assert_eq!(res, Ok(true));
+
+    let (tx, rx) = channel::<()>();
+    let s = StreamData { _data: tx, stream: stream::iter((0..).map(Ok::<i32, ()>)) };
+    let res = s.all(|i| i < 1).map(move |b| {
+        assert!(rx.recv().is_err());
+        b
+    }).wait();
+    assert_eq!(res, Ok(false));
+}
+
 // #[test]
 // fn or_else_drops_eagerly() {
 //     let (p1, c1) = oneshot::<(), ()>();
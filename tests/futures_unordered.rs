@@ -4,7 +4,7 @@ extern crate futures;
 use std::any::Any;
 
 use futures::sync::oneshot;
-use futures::stream::futures_unordered;
+use futures::stream::{futures_unordered, FuturesUnordered};
 use futures::Future;
 
 mod support;
@@ -25,7 +25,8 @@ fn works_1() {
     c_tx.complete(33);
     assert_eq!(Some(Ok(33)), spawn.wait_stream());
     assert_eq!(Some(Ok(33)), spawn.wait_stream());
-    assert_eq!(None, spawn.wait_stream());
+    assert!(spawn.get_ref().is_empty());
+    assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
 }
 
 #[test]
@@ -67,3 +68,40 @@ fn finished_future_ok() {
     assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
     assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
 }
+
+#[test]
+fn push_into_running_set() {
+    let (a_tx, a_rx) = oneshot::channel::<u32>();
+    let (b_tx, b_rx) = oneshot::channel::<u32>();
+
+    let mut spawn = futures::executor::spawn(FuturesUnordered::new());
+    assert!(spawn.get_ref().is_empty());
+    assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
+
+    spawn.get_mut().push(a_rx);
+    assert_eq!(spawn.get_ref().len(), 1);
+    assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
+
+    spawn.get_mut().push(b_rx);
+    assert_eq!(spawn.get_ref().len(), 2);
+    b_tx.complete(2);
+    assert_eq!(Some(Ok(2)), spawn.wait_stream());
+    assert_eq!(spawn.get_ref().len(), 1);
+
+    a_tx.complete(1);
+    assert_eq!(Some(Ok(1)), spawn.wait_stream());
+    assert!(spawn.get_ref().is_empty());
+    assert!(spawn.poll_stream(support::unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+fn reuses_slots() {
+    let mut spawn = futures::executor::spawn(FuturesUnordered::new());
+    for i in 0..10 {
+        let (tx, rx) = oneshot::channel::<u32>();
+        spawn.get_mut().push(rx);
+        tx.complete(i);
+        assert_eq!(Some(Ok(i)), spawn.wait_stream());
+        assert!(spawn.get_ref().is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+extern crate futures;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::thread;
+
+use futures::{Future, Sink};
+use futures::executor::LocalPool;
+use futures::future::{lazy, ok};
+use futures::stream::Stream;
+use futures::sync::{oneshot, mpsc};
+
+#[test]
+fn run_until_simple() {
+    let mut pool = LocalPool::new();
+    assert_eq!(pool.run_until(ok::<i32, ()>(1)), Ok(1));
+    assert_eq!(pool.run_until(futures::future::err::<(), i32>(2)), Err(2));
+}
+
+#[test]
+fn runs_non_send_tasks() {
+    let mut pool = LocalPool::new();
+    let cnt = Rc::new(Cell::new(0));
+    for _ in 0..10 {
+        let cnt = cnt.clone();
+        pool.spawn_local(lazy(move || {
+            cnt.set(cnt.get() + 1);
+            Ok(())
+        }));
+    }
+    assert_eq!(cnt.get(), 0);
+    pool.run();
+    assert_eq!(cnt.get(), 10);
+}
+
+#[test]
+fn run_until_drives_spawned_tasks() {
+    let mut pool = LocalPool::new();
+    let (tx, rx) = oneshot::channel();
+    pool.spawn_local(lazy(move || {
+        tx.complete(Rc::new(3));
+        Ok(())
+    }));
+    let v = pool.run_until(rx).unwrap();
+    assert_eq!(*v, 3);
+}
+
+#[test]
+fn spawn_from_task() {
+    let mut pool = LocalPool::new();
+    let spawner = pool.spawner();
+    let hits = Rc::new(RefCell::new(Vec::new()));
+    let hits2 = hits.clone();
+    pool.spawn_local(lazy(move || {
+        for i in 0..3 {
+            let hits = hits2.clone();
+            spawner.spawn_local(lazy(move || {
+                hits.borrow_mut().push(i);
+                Ok(())
+            }));
+        }
+        Ok(())
+    }));
+    pool.run();
+    assert_eq!(*hits.borrow(), vec![0, 1, 2]);
+}
+
+#[test]
+fn wakeups_from_other_threads() {
+    let mut pool = LocalPool::new();
+    let (tx, rx) = mpsc::channel::<u32>(1);
+    let sum = Rc::new(Cell::new(0));
+    let sum2 = sum.clone();
+    pool.spawn_local(rx.for_each(move |i| {
+        sum2.set(sum2.get() + i);
+        Ok(())
+    }));
+    let t = thread::spawn(move || {
+        let mut tx = tx;
+        for i in 1..11 {
+            tx = tx.send(i).wait().unwrap();
+        }
+    });
+    pool.run();
+    t.join().unwrap();
+    assert_eq!(sum.get(), 55);
+}
+
+#[test]
+fn run_until_leaves_other_tasks() {
+    let mut pool = LocalPool::new();
+    let (tx, rx) = oneshot::channel::<()>();
+    let done = Rc::new(Cell::new(false));
+    let done2 = done.clone();
+    pool.spawn_local(rx.map(move |()| done2.set(true)).map_err(|_| ()));
+    pool.run_until(ok::<(), ()>(())).unwrap();
+    assert!(!done.get());
+    tx.complete(());
+    pool.run();
+    assert!(done.get());
+}
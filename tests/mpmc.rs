@@ -0,0 +1,164 @@
+#![cfg(feature = "use_std")]
+
+extern crate futures;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use futures::{Future, Stream, Sink, Async, AsyncSink};
+use futures::executor;
+use futures::sync::mpmc;
+
+mod support;
+use support::*;
+
+fn is_send<T: Send>() {}
+
+#[test]
+fn bounds() {
+    is_send::<mpmc::Sender<i32>>();
+    is_send::<mpmc::Receiver<i32>>();
+}
+
+#[test]
+fn send_recv() {
+    let (tx, rx) = mpmc::channel::<i32>(16);
+    let tx = tx.send(1).wait().unwrap();
+    let tx = tx.send(2).wait().unwrap();
+    drop(tx);
+    assert_eq!(rx.collect().wait(), Ok(vec![1, 2]));
+}
+
+#[test]
+fn each_message_goes_to_one_receiver() {
+    let (mut tx, rx1) = mpmc::channel::<i32>(16);
+    let rx2 = rx1.clone();
+
+    for i in 0..4 {
+        assert_eq!(start_send(&mut tx, i, unpark_noop()).ok(), Some(AsyncSink::Ready));
+    }
+    drop(tx);
+
+    let mut rx1 = rx1.wait();
+    assert_eq!(rx1.next(), Some(Ok(0)));
+    assert_eq!(rx1.next(), Some(Ok(1)));
+    assert_eq!(rx2.collect().wait(), Ok(vec![2, 3]));
+    assert_eq!(rx1.next(), None);
+}
+
+#[test]
+fn send_wakes_one_receiver() {
+    let (tx, rx1) = mpmc::channel::<i32>(16);
+    let rx2 = rx1.clone();
+    let mut rx1 = executor::spawn(rx1);
+    let mut rx2 = executor::spawn(rx2);
+    let u1 = unpark_counter();
+    let u2 = unpark_counter();
+    assert!(rx1.poll_stream(u1.clone()).unwrap().is_not_ready());
+    assert!(rx2.poll_stream(u2.clone()).unwrap().is_not_ready());
+
+    let tx = tx.send(1).wait().unwrap();
+    assert_eq!((u1.count(), u2.count()), (1, 0));
+    tx.send(2).wait().unwrap();
+    assert_eq!((u1.count(), u2.count()), (1, 1));
+
+    assert_eq!(rx2.poll_stream(u2.clone()).unwrap(), Async::Ready(Some(1)));
+    assert_eq!(rx1.poll_stream(u1.clone()).unwrap(), Async::Ready(Some(2)));
+    assert_eq!(rx1.poll_stream(u1.clone()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn dropped_receiver_passes_wakeup() {
+    let (tx, rx1) = mpmc::channel::<i32>(16);
+    let rx2 = rx1.clone();
+    let mut rx1 = executor::spawn(rx1);
+    let mut rx2 = executor::spawn(rx2);
+    let u2 = unpark_counter();
+    assert!(rx1.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    assert!(rx2.poll_stream(u2.clone()).unwrap().is_not_ready());
+
+    let _tx = tx.send(1).wait().unwrap();
+    assert_eq!(u2.count(), 0);
+    drop(rx1);
+    assert_eq!(u2.count(), 1);
+    assert_eq!(rx2.poll_stream(u2.clone()).unwrap(), Async::Ready(Some(1)));
+}
+
+#[test]
+fn back_pressure() {
+    let (mut tx, rx1) = mpmc::channel::<i32>(1);
+    let rx2 = rx1.clone();
+    let unpark = unpark_counter();
+
+    // One slot from the buffer plus the sender's guaranteed slot
+    assert_eq!(start_send(&mut tx, 1, unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(start_send(&mut tx, 2, unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(start_send(&mut tx, 3, unpark.clone()).ok(), Some(AsyncSink::NotReady(3)));
+    assert_eq!(unpark.count(), 0);
+
+    // Any receiver popping a message unparks the sender
+    let mut rx2 = rx2.wait();
+    assert_eq!(rx2.next(), Some(Ok(1)));
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(start_send(&mut tx, 3, unpark.clone()).ok(), Some(AsyncSink::Ready));
+    drop(tx);
+
+    assert_eq!(rx1.collect().wait(), Ok(vec![2, 3]));
+}
+
+#[test]
+fn send_after_receivers_dropped() {
+    let (tx, rx1) = mpmc::channel::<i32>(1);
+    let rx2 = rx1.clone();
+    drop(rx1);
+    let tx = tx.send(1).wait().unwrap();
+    drop(rx2);
+    match tx.send(2).wait() {
+        Err(e) => assert_eq!(e.into_inner(), 2),
+        Ok(_) => panic!("send succeeded"),
+    }
+}
+
+#[test]
+fn close_drains_buffer() {
+    let (tx, mut rx) = mpmc::channel::<i32>(4);
+    let tx = tx.send(1).wait().unwrap();
+    rx.close();
+    assert!(tx.send(2).wait().is_err());
+    assert_eq!(rx.collect().wait(), Ok(vec![1]));
+}
+
+#[test]
+fn work_distribution_across_threads() {
+    const N: usize = 1000;
+    let (tx, rx) = mpmc::channel::<usize>(4);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let workers = (0..4).map(|_| {
+        let rx = rx.clone();
+        let seen = seen.clone();
+        thread::spawn(move || {
+            rx.for_each(|i| {
+                seen.lock().unwrap().push(i);
+                Ok(())
+            }).wait().unwrap();
+        })
+    }).collect::<Vec<_>>();
+    drop(rx);
+
+    let senders = (0..2).map(|t| {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let items = (0..N).filter(|i| i % 2 == t).map(Ok);
+            assert!(tx.send_all(futures::stream::iter(items)).wait().is_ok());
+        })
+    }).collect::<Vec<_>>();
+    drop(tx);
+
+    for t in senders.into_iter().chain(workers) {
+        t.join().unwrap();
+    }
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(seen, (0..N).collect::<Vec<_>>());
+}
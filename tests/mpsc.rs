@@ -91,7 +91,7 @@ fn send_recv_threads_no_capacity() {
 
     let t = thread::spawn(move|| {
         tx = tx.send(1).wait().unwrap();
-        tx = tx.send(2).wait().unwrap();
+        tx.send(2).wait().unwrap();
     });
 
     thread::sleep(Duration::from_millis(100));
@@ -151,11 +151,11 @@ fn stress_shared_unbounded() {
     });
 
     for _ in 0..NTHREADS {
-        let mut tx = tx.clone();
+        let tx = tx.clone();
 
         thread::spawn(move|| {
             for _ in 0..AMT {
-                mpsc::UnboundedSender::send(&mut tx, 1).unwrap();
+                mpsc::UnboundedSender::send(&tx, 1).unwrap();
             }
         });
     }
@@ -278,9 +278,124 @@ fn stress_receiver_multi_task_bounded_hard() {
     assert_eq!(AMT, n.load(Ordering::Relaxed));
 }
 
+#[test]
+fn try_send_full_and_disconnected() {
+    let (mut tx, mut rx) = mpsc::channel::<i32>(0);
+
+    // The sender's guaranteed slot
+    assert!(tx.try_send(1).is_ok());
+    let err = tx.try_send(2).unwrap_err();
+    assert!(err.is_full());
+    assert_eq!(err.into_inner(), 2);
+
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert!(tx.try_send(2).is_ok());
+
+    drop(rx);
+    let err = tx.try_send(3).unwrap_err();
+    assert!(err.is_disconnected());
+    assert_eq!(err.into_inner(), 3);
+}
+
+#[test]
+fn try_send_then_wait_for_capacity() {
+    let (mut tx, rx) = mpsc::channel::<i32>(1);
+    assert!(tx.try_send(1).is_ok());
+    assert!(tx.try_send(2).is_ok());
+    assert!(tx.try_send(3).unwrap_err().is_full());
+
+    let t = thread::spawn(move || rx.collect().wait());
+    let tx = tx.send(3).wait().unwrap();
+    drop(tx);
+    assert_eq!(t.join().unwrap(), Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn try_recv() {
+    let (mut tx, mut rx) = mpsc::channel::<i32>(4);
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Empty));
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    assert_eq!(rx.try_recv(), Ok(1));
+    drop(tx);
+    assert_eq!(rx.try_recv(), Ok(2));
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+
+    let (_tx, mut rx) = mpsc::unbounded::<i32>();
+    rx.close();
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+}
+
+#[test]
+fn poll_ready() {
+    let (mut tx, mut rx) = mpsc::channel::<i32>(0);
+
+    // Run on a task context
+    lazy(move || {
+        assert!(tx.poll_ready().unwrap().is_ready());
+        assert!(is_ready(&tx.start_send(1).unwrap()));
+        assert!(tx.poll_ready().unwrap().is_not_ready());
+
+        assert_eq!(rx.poll().unwrap(), Async::Ready(Some(1)));
+        assert!(tx.poll_ready().unwrap().is_ready());
+
+        drop(rx);
+        assert!(tx.poll_ready().is_err());
+
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn introspection() {
+    let (mut tx, mut rx) = mpsc::channel::<i32>(2);
+    assert_eq!(tx.len(), 0);
+    assert!(rx.is_empty());
+    assert_eq!(tx.capacity(), 3);
+    assert_eq!(rx.sender_count(), 1);
+
+    let mut tx2 = tx.clone();
+    assert_eq!(rx.capacity(), 4);
+    assert_eq!(tx.sender_count(), 2);
+
+    tx.try_send(1).unwrap();
+    tx2.try_send(2).unwrap();
+    assert_eq!(rx.len(), 2);
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(tx.len(), 1);
+
+    // The end of the stream isn't counted as a message
+    drop(tx2);
+    assert!(!rx.is_closed());
+    drop(tx);
+    assert_eq!(rx.sender_count(), 0);
+    assert!(rx.is_closed());
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx.try_recv(), Ok(2));
+    assert_eq!(rx.len(), 0);
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    assert_eq!(rx.len(), 0);
+}
+
+#[test]
+fn introspection_closed_by_receiver() {
+    let (tx, mut rx) = mpsc::unbounded::<i32>();
+    mpsc::UnboundedSender::send(&tx, 1).unwrap();
+    assert!(!tx.is_closed());
+    rx.close();
+    assert!(tx.is_closed());
+    assert!(rx.is_closed());
+    drop(tx);
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx.sender_count(), 0);
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(rx.len(), 0);
+}
+
 fn is_ready<T>(res: &AsyncSink<T>) -> bool {
-    match *res {
-        AsyncSink::Ready => true,
-        _ => false,
+    if let AsyncSink::Ready = *res {
+        return true
     }
+    false
 }
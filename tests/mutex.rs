@@ -0,0 +1,189 @@
+extern crate futures;
+
+use std::thread;
+
+use futures::Async;
+use futures::executor;
+use futures::stream::{self, Stream};
+use futures::future::Future;
+use futures::sync::{Mutex, RwLock};
+
+mod support;
+use support::*;
+
+#[test]
+fn mutex_smoke() {
+    let mutex = Mutex::new(1);
+    let mut guard = mutex.lock().wait().unwrap();
+    assert_eq!(*guard, 1);
+    *guard = 2;
+
+    assert!(mutex.try_lock().is_none());
+    let mut waiter = executor::spawn(mutex.lock());
+    assert!(waiter.poll_future(unpark_noop()).unwrap().is_not_ready());
+    drop(guard);
+
+    match waiter.poll_future(unpark_noop()).unwrap() {
+        Async::Ready(guard) => assert_eq!(*guard, 2),
+        Async::NotReady => panic!("not ready"),
+    }
+    assert!(mutex.try_lock().is_some());
+}
+
+#[test]
+fn mutex_fifo() {
+    let mutex = Mutex::new(Vec::new());
+    let guard = mutex.try_lock().unwrap();
+
+    let mut waiters = (0..3).map(|i| {
+        executor::spawn(mutex.lock().map(move |mut v| v.push(i)))
+    }).collect::<Vec<_>>();
+    let unparks = (0..3).map(|_| unpark_counter()).collect::<Vec<_>>();
+    for i in (0..3).rev() {
+        let res = waiters[i].poll_future(unparks[i].clone()).unwrap();
+        assert!(res.is_not_ready());
+    }
+
+    // New lockers queue up behind the existing waiters
+    assert!(mutex.try_lock().is_none());
+
+    drop(guard);
+    assert_eq!(unparks.iter().map(|u| u.count()).collect::<Vec<_>>(), [0, 0, 1]);
+    assert!(waiters[1].poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(waiters[2].poll_future(unpark_noop()).unwrap().is_ready());
+    assert!(waiters[1].poll_future(unpark_noop()).unwrap().is_ready());
+    assert!(waiters[0].poll_future(unpark_noop()).unwrap().is_ready());
+    assert_eq!(*mutex.try_lock().unwrap(), vec![2, 1, 0]);
+}
+
+#[test]
+fn mutex_dropped_waiter_passes_wakeup() {
+    let mutex = Mutex::new(());
+    let guard = mutex.try_lock().unwrap();
+
+    let mut a = executor::spawn(mutex.lock());
+    let mut b = executor::spawn(mutex.lock());
+    let unpark = unpark_counter();
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(unpark.clone()).unwrap().is_not_ready());
+
+    drop(guard);
+    assert_eq!(unpark.count(), 0);
+    drop(a);
+    assert_eq!(unpark.count(), 1);
+    assert!(b.poll_future(unpark.clone()).unwrap().is_ready());
+}
+
+#[test]
+fn mutex_guard_across_and_then() {
+    let mutex = Mutex::new(0);
+    let res = mutex.lock().and_then(|mut guard| {
+        *guard += 1;
+        Ok(guard)
+    }).and_then(|mut guard| {
+        *guard += 1;
+        Ok(*guard)
+    }).wait();
+    assert_eq!(res, Ok(2));
+}
+
+#[test]
+fn mutex_concurrent() {
+    const N: usize = 1000;
+    let mutex = Mutex::new(0);
+
+    let threads = (0..4).map(|_| {
+        let mutex = mutex.clone();
+        thread::spawn(move || {
+            stream::iter((0..N).map(Ok::<_, ()>)).for_each(|_| {
+                mutex.lock().map(|mut n| *n += 1)
+            }).wait()
+        })
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap().unwrap();
+    }
+    assert_eq!(*mutex.try_lock().unwrap(), 4 * N);
+}
+
+#[test]
+fn rwlock_readers_share() {
+    let lock = RwLock::new(1);
+    let a = lock.read().wait().unwrap();
+    let b = lock.try_read().unwrap();
+    assert_eq!(*a + *b, 2);
+    assert!(lock.try_write().is_none());
+
+    let mut writer = executor::spawn(lock.write());
+    let unpark = unpark_counter();
+    assert!(writer.poll_future(unpark.clone()).unwrap().is_not_ready());
+
+    // Readers queue up behind a waiting writer
+    assert!(lock.try_read().is_none());
+
+    drop(a);
+    assert_eq!(unpark.count(), 0);
+    drop(b);
+    assert_eq!(unpark.count(), 1);
+    match writer.poll_future(unpark.clone()).unwrap() {
+        Async::Ready(mut guard) => *guard = 2,
+        Async::NotReady => panic!("not ready"),
+    }
+    assert_eq!(*lock.try_read().unwrap(), 2);
+}
+
+#[test]
+fn rwlock_readers_let_in_together() {
+    let lock = RwLock::new(());
+    let guard = lock.try_write().unwrap();
+
+    let mut r1 = executor::spawn(lock.read());
+    let mut r2 = executor::spawn(lock.read());
+    let mut w = executor::spawn(lock.write());
+    let u1 = unpark_counter();
+    let u2 = unpark_counter();
+    let uw = unpark_counter();
+    assert!(r1.poll_future(u1.clone()).unwrap().is_not_ready());
+    assert!(r2.poll_future(u2.clone()).unwrap().is_not_ready());
+    assert!(w.poll_future(uw.clone()).unwrap().is_not_ready());
+
+    drop(guard);
+    assert_eq!(u1.count(), 1);
+    let g1 = match r1.poll_future(u1.clone()).unwrap() {
+        Async::Ready(g) => g,
+        Async::NotReady => panic!("not ready"),
+    };
+    assert_eq!(u2.count(), 1);
+    let g2 = match r2.poll_future(u2.clone()).unwrap() {
+        Async::Ready(g) => g,
+        Async::NotReady => panic!("not ready"),
+    };
+    assert_eq!(uw.count(), 0);
+    drop(g1);
+    drop(g2);
+    assert_eq!(uw.count(), 1);
+    assert!(w.poll_future(uw.clone()).unwrap().is_ready());
+}
+
+#[test]
+fn rwlock_concurrent() {
+    const N: usize = 1000;
+    let lock = RwLock::new(0);
+
+    let threads = (0..4).map(|_| {
+        let lock = lock.clone();
+        thread::spawn(move || {
+            stream::iter((0..N).map(Ok::<_, ()>)).for_each(|i| {
+                let lock2 = lock.clone();
+                lock.read().and_then(move |r| {
+                    drop(r);
+                    lock2.write()
+                }).map(move |mut w| *w += i)
+            }).wait()
+        })
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap().unwrap();
+    }
+    assert_eq!(*lock.try_read().unwrap(), 4 * (0..N).sum::<usize>());
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "use_std")]
+
+extern crate futures;
+
+use std::thread;
+
+use futures::{Future, Stream, Sink, Async, AsyncSink};
+use futures::executor;
+use futures::sync::priority;
+
+mod support;
+use support::*;
+
+fn is_send<T: Send>() {}
+
+#[test]
+fn bounds() {
+    is_send::<priority::Sender<u8, i32>>();
+    is_send::<priority::Receiver<u8, i32>>();
+}
+
+#[test]
+fn highest_priority_first() {
+    let (mut tx, rx) = priority::channel::<u8, &str>(16);
+    for &item in &[(1, "a"), (3, "b"), (2, "c"), (3, "d"), (1, "e")] {
+        assert_eq!(start_send(&mut tx, item, unpark_noop()).ok(), Some(AsyncSink::Ready));
+    }
+    drop(tx);
+
+    // FIFO within each priority level
+    assert_eq!(rx.collect().wait(), Ok(vec!["b", "d", "c", "a", "e"]));
+}
+
+#[test]
+fn send_wakes_receiver() {
+    let (tx, rx) = priority::channel::<u8, i32>(16);
+    let mut rx = executor::spawn(rx);
+    let unpark = unpark_counter();
+    assert!(rx.poll_stream(unpark.clone()).unwrap().is_not_ready());
+
+    let tx = tx.send((0, 1)).wait().unwrap();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(Some(1)));
+    drop(tx);
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn back_pressure() {
+    let (mut tx, rx) = priority::channel::<u8, i32>(1);
+    let mut rx = executor::spawn(rx);
+    let unpark = unpark_counter();
+
+    // One slot from the buffer plus the sender's guaranteed slot
+    assert_eq!(start_send(&mut tx, (0, 1), unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(start_send(&mut tx, (0, 2), unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(start_send(&mut tx, (9, 3), unpark.clone()).ok(),
+               Some(AsyncSink::NotReady((9, 3))));
+    assert_eq!(unpark.count(), 0);
+
+    assert_eq!(rx.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(1)));
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(start_send(&mut tx, (9, 3), unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(rx.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(3)));
+    assert_eq!(rx.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(2)));
+}
+
+#[test]
+fn close_then_drain() {
+    let (tx, mut rx) = priority::channel::<u8, i32>(4);
+    let tx = tx.send((1, 1)).wait().unwrap();
+    let tx = tx.send((2, 2)).wait().unwrap();
+    rx.close();
+    match tx.send((3, 3)).wait() {
+        Err(e) => assert_eq!(e.into_inner(), (3, 3)),
+        Ok(_) => panic!("send succeeded"),
+    }
+    assert_eq!(rx.collect().wait(), Ok(vec![2, 1]));
+}
+
+#[test]
+fn close_wakes_parked_sender() {
+    let (mut tx, mut rx) = priority::channel::<u8, i32>(0);
+    let unpark = unpark_counter();
+    assert_eq!(start_send(&mut tx, (0, 1), unpark.clone()).ok(), Some(AsyncSink::Ready));
+    assert_eq!(start_send(&mut tx, (0, 2), unpark.clone()).ok(),
+               Some(AsyncSink::NotReady((0, 2))));
+    rx.close();
+    assert_eq!(unpark.count(), 1);
+    assert!(start_send(&mut tx, (0, 2), unpark.clone()).is_err());
+}
+
+#[test]
+fn send_after_receiver_dropped() {
+    let (tx, rx) = priority::channel::<u8, i32>(4);
+    drop(rx);
+    assert!(tx.send((0, 1)).wait().is_err());
+}
+
+#[test]
+fn multiple_senders_across_threads() {
+    const N: usize = 1000;
+    let (tx, rx) = priority::channel::<usize, usize>(4);
+
+    let threads = (0..4).map(|t| {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let items = (0..N).map(move |i| Ok((t, i)));
+            assert!(tx.send_all(futures::stream::iter(items)).wait().is_ok());
+        })
+    }).collect::<Vec<_>>();
+    drop(tx);
+
+    let mut items = rx.collect().wait().unwrap();
+    for t in threads {
+        t.join().unwrap();
+    }
+    items.sort();
+    let mut expected = (0..4).flat_map(|_| 0..N).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(items, expected);
+}
@@ -0,0 +1,113 @@
+extern crate futures;
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use futures::executor;
+use futures::future::{self, Future, RetryPolicy, FixedBackoff, ExponentialBackoff};
+use futures::timer::{self, Timer, MockClock};
+
+mod support;
+use support::*;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn succeeds_first_time() {
+    let calls = Cell::new(0);
+    let f = future::retry(FixedBackoff::new(ms(1)), || {
+        calls.set(calls.get() + 1);
+        Ok::<u32, u32>(1)
+    });
+    assert_eq!(f.wait(), Ok(1));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn waits_between_attempts() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+    let calls = Cell::new(0);
+    let f = timer::with_default(&timer, || {
+        future::retry(FixedBackoff::new(ms(10)), || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 { Err(calls.get()) } else { Ok(calls.get()) }
+        })
+    });
+    let mut f = executor::spawn(f);
+
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(calls.get(), 1);
+    clock.advance(ms(9));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(calls.get(), 1);
+    clock.advance(ms(1));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(calls.get(), 2);
+
+    let unpark = unpark_counter();
+    assert!(f.poll_future(unpark.clone()).unwrap().is_not_ready());
+    clock.advance(ms(10));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(f.poll_future(unpark_noop()).unwrap(), futures::Async::Ready(3));
+}
+
+#[test]
+fn max_attempts_returns_last_error() {
+    let calls = Cell::new(0);
+    let policy = FixedBackoff::new(ms(0)).max_attempts(3);
+    let f = future::retry(policy, || {
+        calls.set(calls.get() + 1);
+        Err::<(), u32>(calls.get())
+    });
+    assert_eq!(f.wait(), Err(3));
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn max_attempts_clone_and_debug_without_error_bounds() {
+    // Neither `Clone` nor `Debug`
+    struct Opaque;
+
+    let policy = RetryPolicy::<Opaque>::max_attempts(FixedBackoff::new(ms(0)), 3);
+    let mut copy = policy.clone();
+    assert!(format!("{:?}", policy).contains("MaxAttempts"));
+    assert!(copy.retry(3, &Opaque).is_none());
+}
+
+#[test]
+fn retry_if_skips_fatal_errors() {
+    let calls = Cell::new(0);
+    let policy = FixedBackoff::new(ms(0)).retry_if(|e: &&str| *e != "fatal");
+    let f = future::retry(policy, || {
+        calls.set(calls.get() + 1);
+        if calls.get() < 3 { Err::<(), _>("again") } else { Err("fatal") }
+    });
+    assert_eq!(f.wait(), Err("fatal"));
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn exponential_delays() {
+    let mut policy = ExponentialBackoff::new(ms(10)).max_delay(ms(50));
+    let delays = (1..6).map(|i| RetryPolicy::<()>::retry(&mut policy, i, &()))
+        .collect::<Vec<_>>();
+    assert_eq!(delays, [Some(ms(10)), Some(ms(20)), Some(ms(40)), Some(ms(50)), Some(ms(50))]);
+
+    let mut policy = ExponentialBackoff::new(ms(10)).factor(3).jitter();
+    for &max in [10, 30, 90, 270].iter() {
+        let delay = RetryPolicy::<()>::retry(&mut policy, 1, &()).unwrap();
+        assert!(delay <= ms(max));
+    }
+}
+
+#[test]
+fn exponential_delay_stops_growing_on_overflow() {
+    let huge = Duration::from_secs(1 << 40);
+    let mut policy = ExponentialBackoff::new(huge);
+    assert_eq!(RetryPolicy::<()>::retry(&mut policy, 1, &()), Some(huge));
+    assert_eq!(RetryPolicy::<()>::retry(&mut policy, 2, &()), Some(huge));
+}
@@ -1,6 +1,16 @@
 extern crate futures;
 
+use std::cell::Cell;
+use std::rc::Rc;
+
+use futures::{Async, Poll};
+use futures::executor;
 use futures::future::*;
+use futures::stream::{self, Stream};
+use futures::sync::{mpsc, oneshot};
+
+mod support;
+use support::*;
 
 #[test]
 fn smoke() {
@@ -24,3 +34,100 @@ fn smoke() {
 
     assert!(v.len() == 0);
 }
+
+// A stream which counts how many times it's been polled
+struct Counted<S> {
+    stream: S,
+    polls: Rc<Cell<usize>>,
+}
+
+impl<S: Stream> Stream for Counted<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<S::Item>, S::Error> {
+        self.polls.set(self.polls.get() + 1);
+        self.stream.poll()
+    }
+}
+
+#[test]
+fn stream_merges_and_removes_finished() {
+    let all = stream::select_all(vec![
+        stream::iter(vec![Ok::<u32, u32>(1), Err(2)]),
+        stream::iter(vec![Ok(3)]),
+    ]);
+    assert_eq!(all.len(), 2);
+    let mut all = executor::spawn(all);
+    let mut items = Vec::new();
+    loop {
+        match all.poll_stream(unpark_noop()) {
+            Ok(Async::Ready(Some(i))) => items.push(Ok(i)),
+            Err(e) => items.push(Err(e)),
+            Ok(Async::Ready(None)) => panic!("select_all finished"),
+            Ok(Async::NotReady) => break,
+        }
+    }
+    items.sort();
+    assert_eq!(items, [Ok(1), Ok(3), Err(2)]);
+    assert!(all.get_ref().is_empty());
+}
+
+#[test]
+fn stream_push_while_running() {
+    let (tx1, rx1) = mpsc::unbounded::<u32>();
+    let mut all = executor::spawn(stream::SelectAll::new());
+    assert!(all.poll_stream(unpark_panic()).unwrap().is_not_ready());
+
+    all.get_mut().push(rx1);
+    let unpark = unpark_counter();
+    assert!(all.poll_stream(unpark.clone()).unwrap().is_not_ready());
+    tx1.send(1).unwrap();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(all.poll_stream(unpark_noop()), Ok(Async::Ready(Some(1))));
+
+    let (tx2, rx2) = mpsc::unbounded::<u32>();
+    all.get_mut().push(rx2);
+    tx2.send(2).unwrap();
+    drop(tx1);
+    assert_eq!(all.poll_stream(unpark_noop()), Ok(Async::Ready(Some(2))));
+    assert!(all.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(all.get_ref().len(), 1);
+}
+
+#[test]
+fn stream_only_polls_woken_members() {
+    let polls = (0..3).map(|_| Rc::new(Cell::new(0))).collect::<Vec<_>>();
+    let mut txs = Vec::new();
+    let mut all = stream::SelectAll::new();
+    for p in polls.iter() {
+        let (tx, rx) = mpsc::unbounded::<u32>();
+        txs.push(tx);
+        all.push(Counted { stream: rx, polls: p.clone() });
+    }
+    let mut all = executor::spawn(all);
+    assert!(all.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(polls.iter().map(|p| p.get()).collect::<Vec<_>>(), [1, 1, 1]);
+
+    txs[1].send(5).unwrap();
+    assert_eq!(all.poll_stream(unpark_noop()), Ok(Async::Ready(Some(5))));
+    assert!(all.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    assert_eq!(polls.iter().map(|p| p.get()).collect::<Vec<_>>(), [1, 3, 1]);
+}
+
+#[test]
+fn stream_of_futures() {
+    let (tx1, rx1) = oneshot::channel::<u32>();
+    let (tx2, rx2) = oneshot::channel::<u32>();
+    let mut all = executor::spawn(stream::select_all(vec![
+        rx1.into_stream(),
+        rx2.into_stream(),
+    ]));
+    assert!(all.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    tx2.complete(2);
+    assert_eq!(all.poll_stream(unpark_noop()), Ok(Async::Ready(Some(2))));
+    tx1.complete(1);
+    assert_eq!(all.poll_stream(unpark_noop()), Ok(Async::Ready(Some(1))));
+    assert!(all.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    assert!(all.get_ref().is_empty());
+}
@@ -0,0 +1,104 @@
+extern crate futures;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use futures::executor;
+use futures::stream::{self, Stream};
+use futures::future::Future;
+use futures::sync::Semaphore;
+
+mod support;
+use support::*;
+
+#[test]
+fn smoke() {
+    let sem = Semaphore::new(3);
+    let a = sem.acquire(2).wait().unwrap();
+    assert_eq!(a.permits(), 2);
+    assert_eq!(sem.available_permits(), 1);
+    assert!(sem.try_acquire(2).is_none());
+    let b = sem.try_acquire(1).unwrap();
+    assert_eq!(sem.available_permits(), 0);
+    drop(a);
+    drop(b);
+    assert_eq!(sem.available_permits(), 3);
+}
+
+#[test]
+fn waiters_are_fifo() {
+    let sem = Semaphore::new(2);
+    let all = sem.try_acquire(2).unwrap();
+
+    let mut big = executor::spawn(sem.acquire(2));
+    let mut small = executor::spawn(sem.acquire(1));
+    let u_big = unpark_counter();
+    let u_small = unpark_counter();
+    assert!(big.poll_future(u_big.clone()).unwrap().is_not_ready());
+    assert!(small.poll_future(u_small.clone()).unwrap().is_not_ready());
+
+    // A small request doesn't jump ahead of the big one
+    sem.add_permits(1);
+    assert_eq!(u_big.count(), 0);
+    assert!(sem.try_acquire(1).is_none());
+    assert!(small.poll_future(u_small.clone()).unwrap().is_not_ready());
+
+    drop(all);
+    assert_eq!(u_big.count(), 1);
+    let permit = match big.poll_future(u_big.clone()).unwrap() {
+        futures::Async::Ready(p) => p,
+        futures::Async::NotReady => panic!("not ready"),
+    };
+
+    // One permit is left over for the next waiter
+    assert_eq!(u_small.count(), 1);
+    assert!(small.poll_future(u_small.clone()).unwrap().is_ready());
+    drop(permit);
+    assert_eq!(sem.available_permits(), 3);
+}
+
+#[test]
+fn dropped_waiter_unblocks_queue() {
+    let sem = Semaphore::new(1);
+    let permit = sem.try_acquire(1).unwrap();
+    let mut a = executor::spawn(sem.acquire(5));
+    let mut b = executor::spawn(sem.acquire(1));
+    let u = unpark_counter();
+    assert!(a.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(b.poll_future(u.clone()).unwrap().is_not_ready());
+
+    drop(permit);
+    assert_eq!(u.count(), 0);
+    drop(a);
+    assert_eq!(u.count(), 1);
+    assert!(b.poll_future(u.clone()).unwrap().is_ready());
+}
+
+#[test]
+fn limits_concurrency() {
+    const N: usize = 500;
+    let sem = Semaphore::new(2);
+    let active = Arc::new(AtomicUsize::new(0));
+
+    let threads = (0..4).map(|_| {
+        let sem = sem.clone();
+        let active = active.clone();
+        thread::spawn(move || {
+            stream::iter((0..N).map(Ok::<_, ()>)).for_each(|_| {
+                let active = active.clone();
+                sem.acquire(1).map(move |permit| {
+                    let prev = active.fetch_add(1, Ordering::SeqCst);
+                    assert!(prev < 2);
+                    thread::yield_now();
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    drop(permit);
+                })
+            }).wait()
+        })
+    }).collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap().unwrap();
+    }
+    assert_eq!(sem.available_permits(), 2);
+}
@@ -82,6 +82,55 @@ fn or_else() {
     }).collect(), Ok(vec![1, 2, 3]));
 }
 
+#[test]
+fn count() {
+    assert_done(|| list().count(), Ok(3));
+    assert_done(|| err_list().count(), Err(3));
+    assert_done(|| iter(Vec::<Result<i32, u32>>::new()).count(), Ok(0));
+}
+
+#[test]
+fn any_all() {
+    assert_done(|| list().any(|a| a == 2), Ok(true));
+    assert_done(|| list().any(|a| a == 4), Ok(false));
+    assert_done(|| err_list().any(|a| a == 4), Err(3));
+    assert_done(|| list().all(|a| a < 4), Ok(true));
+    assert_done(|| list().all(|a| a < 2), Ok(false));
+    assert_done(|| err_list().all(|a| a < 4), Err(3));
+
+    // Both short-circuit without looking at any more items
+    let mut seen = 0;
+    assert_done(|| iter(vec![Ok(1), Ok(2), Err(3)]).any(|a| { seen += 1; a == 1 }),
+                Ok(true));
+    assert_eq!(seen, 1);
+    assert_done(|| iter(vec![Ok(1), Ok(2), Err(3)]).all(|a| a == 2), Ok(false));
+}
+
+#[test]
+fn last() {
+    assert_done(|| list().last(), Ok(Some(3)));
+    assert_done(|| err_list().last(), Err(3));
+    assert_done(|| iter(Vec::<Result<i32, u32>>::new()).last(), Ok(None));
+}
+
+#[test]
+fn min_max_by_key() {
+    let items = || iter(vec![Ok((1, 'a')), Ok((3, 'b')), Ok((1, 'c')), Ok((3, 'd'))]);
+    assert_done(|| items().min_by_key(|x| x.0), Ok::<_, u32>(Some((1, 'a'))));
+    assert_done(|| items().max_by_key(|x| x.0), Ok::<_, u32>(Some((3, 'd'))));
+    assert_done(|| err_list().min_by_key(|x| *x), Err(3));
+    assert_done(|| err_list().max_by_key(|x| *x), Err(3));
+    assert_done(|| iter(Vec::<Result<i32, u32>>::new()).max_by_key(|x| *x), Ok(None));
+}
+
+#[test]
+fn concat() {
+    assert_done(|| list().map(|a| vec![a; a as usize]).concat(),
+                Ok(vec![1, 2, 2, 3, 3, 3]));
+    assert_done(|| err_list().map(|a| vec![a]).concat(), Err(3));
+    assert_done(|| iter(Vec::<Result<Vec<u8>, u32>>::new()).concat(), Ok(vec![]));
+}
+
 #[test]
 fn flatten() {
     assert_done(|| list().map(|_| list()).flatten().collect(),
@@ -89,6 +138,44 @@ fn flatten() {
 
 }
 
+#[test]
+fn flat_map() {
+    assert_done(|| list().flat_map(|a| iter((0..a).map(Ok::<i32, u32>))).collect(),
+                Ok(vec![0, 0, 1, 0, 1, 2]));
+    assert_done(|| err_list().flat_map(|a| iter(vec![Ok::<i32, u32>(a)])).collect(), Err(3));
+}
+
+#[test]
+fn inspect() {
+    let mut seen = Vec::new();
+    assert_eq!(err_list().inspect(|a| seen.push(*a)).collect().wait(), Err(3));
+    assert_eq!(seen, [1, 2]);
+}
+
+#[test]
+fn enumerate() {
+    assert_done(|| list().enumerate().collect(), Ok(vec![(0, 1), (1, 2), (2, 3)]));
+
+    let mut s = iter(vec![Ok(1), Err(2), Ok(3)]).enumerate().wait();
+    assert_eq!(s.next(), Some(Ok((0, 1))));
+    assert_eq!(s.next(), Some(Err(2)));
+    assert_eq!(s.next(), Some(Ok((1, 3))));
+    assert_eq!(s.next(), None);
+}
+
+#[test]
+fn scan() {
+    assert_done(|| list().scan(0, |sum, a| { *sum += a; Some(*sum) }).collect(),
+                Ok(vec![1, 3, 6]));
+    assert_done(|| list().scan((), |_, a| if a < 3 { Some(a) } else { None }).collect(),
+                Ok(vec![1, 2]));
+
+    // The underlying stream isn't polled again once the closure is done
+    let mut s = iter(vec![Ok(1), Ok(2)]).scan((), |_, _| None::<i32>).wait();
+    assert_eq!(s.next(), None::<Result<i32, u32>>);
+    assert_eq!(s.next(), None);
+}
+
 #[test]
 fn skip() {
     assert_done(|| list().skip(2).collect(), Ok(vec![3]));
@@ -137,6 +224,69 @@ fn take_passes_errors_through() {
     assert_eq!(s.next(), None);
 }
 
+#[test]
+fn for_each_concurrent() {
+    let (txs, rxs): (Vec<_>, Vec<_>) = (0..4).map(|_| oneshot::channel::<()>()).unzip();
+    let mut started = Vec::new();
+    let mut rxs = rxs.into_iter().map(Some).collect::<Vec<_>>();
+    {
+        let f = iter((0..4).map(Ok::<usize, oneshot::Canceled>)).for_each_concurrent(2, |i| {
+            started.push(i);
+            rxs[i].take().unwrap()
+        });
+        let mut f = executor::spawn(f);
+        assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+        let mut txs = txs.into_iter();
+        let (tx0, tx1) = (txs.next().unwrap(), txs.next().unwrap());
+        tx1.complete(());
+        assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+        tx0.complete(());
+        for tx in txs {
+            tx.complete(());
+        }
+        assert!(f.poll_future(unpark_noop()).unwrap().is_ready());
+    }
+    assert_eq!(started, [0, 1, 2, 3]);
+}
+
+#[test]
+fn for_each_concurrent_limit() {
+    let (tx, rx) = mpsc::unbounded::<u32>();
+    let mut in_flight = Vec::new();
+    {
+        let f = rx.for_each_concurrent(2, |_| {
+            let (tx, rx) = oneshot::channel::<()>();
+            in_flight.push(tx);
+            rx.map_err(|_| ())
+        });
+        let mut f = executor::spawn(f);
+        for i in 0..5 {
+            mpsc::UnboundedSender::send(&tx, i).unwrap();
+        }
+        assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+    }
+    assert_eq!(in_flight.len(), 2);
+}
+
+#[test]
+fn for_each_concurrent_first_error_cancels() {
+    let (mut tx1, rx1) = oneshot::channel::<()>();
+    let (tx2, rx2) = oneshot::channel::<()>();
+    let mut rxs = vec![Some(rx1.map_err(|_| 1).boxed()), Some(rx2.map_err(|_| 2).boxed())];
+    let mut f = executor::spawn(iter(vec![Ok(0), Ok(1)]).for_each_concurrent(2, |i| {
+        rxs[i].take().unwrap()
+    }));
+    assert!(f.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    drop(tx2);
+    assert_eq!(f.poll_future(unpark_noop()), Err(2));
+
+    // The other future was dropped along with the stream
+    let mut tx1 = executor::spawn(futures::future::poll_fn(move || tx1.poll_cancel()));
+    assert!(tx1.poll_future(unpark_noop()).unwrap().is_ready());
+}
+
 #[test]
 fn peekable() {
     assert_done(|| list().peekable().collect(), Ok(vec![1, 2, 3]));
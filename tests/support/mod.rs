@@ -2,10 +2,11 @@
 
 use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 
-use futures::{Future, IntoFuture, Async, Poll};
-use futures::future::FutureResult;
+use futures::{Future, IntoFuture, Async, Poll, StartSend};
+use futures::future::{self, FutureResult};
 use futures::stream::Stream;
 use futures::executor::{self, Unpark};
 use futures::task;
@@ -90,6 +91,40 @@ pub fn unpark_noop() -> Arc<Unpark> {
     Arc::new(Foo)
 }
 
+pub struct UnparkCounter(AtomicUsize);
+
+impl UnparkCounter {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Unpark for UnparkCounter {
+    fn unpark(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+pub fn unpark_counter() -> Arc<UnparkCounter> {
+    Arc::new(UnparkCounter(AtomicUsize::new(0)))
+}
+
+/// Calls `start_send` on `sink` from within a task unparked through `unpark`
+pub fn start_send<S>(sink: &mut S, item: S::SinkItem, unpark: Arc<Unpark>)
+                     -> StartSend<S::SinkItem, S::SinkError>
+    where S: ::futures::Sink
+{
+    let mut item = Some(item);
+    let mut task = executor::spawn(future::poll_fn(|| {
+        sink.start_send(item.take().unwrap()).map(Async::Ready)
+    }));
+    match task.poll_future(unpark) {
+        Ok(Async::Ready(ret)) => Ok(ret),
+        Ok(Async::NotReady) => unreachable!(),
+        Err(e) => Err(e),
+    }
+}
+
 pub trait ForgetExt {
     fn forget(self);
 }
@@ -0,0 +1,428 @@
+extern crate futures;
+
+use std::time::{Duration, Instant};
+
+use futures::{Async, AsyncSink, Future, Stream};
+use futures::executor;
+use futures::future::empty;
+use futures::stream;
+use futures::sync::{oneshot, mpsc};
+use futures::timer::{self, Timer, MockClock, TimeoutError};
+
+mod support;
+use support::*;
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn delay_mock_clock() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut delay = executor::spawn(timer.delay(ms(10)));
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(9));
+    timer.turn();
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(1));
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_ready());
+}
+
+#[test]
+fn turn_unparks() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut delay = executor::spawn(timer.delay(ms(100)));
+    let unpark = support::unpark_counter();
+    assert!(delay.poll_future(unpark.clone()).unwrap().is_not_ready());
+    assert_eq!(unpark.count(), 0);
+    clock.advance(ms(50));
+    timer.turn();
+    assert_eq!(unpark.count(), 0);
+    clock.advance(ms(50));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+    assert!(delay.poll_future(unpark.clone()).unwrap().is_ready());
+}
+
+#[test]
+fn far_future_delay() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut delay = executor::spawn(timer.delay(Duration::from_secs(3600)));
+    let unpark = support::unpark_counter();
+    assert!(delay.poll_future(unpark.clone()).unwrap().is_not_ready());
+    for _ in 0..10 {
+        clock.advance(ms(300));
+        timer.turn();
+    }
+    assert_eq!(unpark.count(), 0);
+    clock.advance(Duration::from_secs(3600));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+}
+
+#[test]
+fn delay_reset() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut delay = executor::spawn(timer.delay(ms(10)));
+    clock.advance(ms(10));
+    timer.turn();
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_ready());
+
+    let at = timer.now() + ms(10);
+    delay.get_mut().reset(at);
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(10));
+    assert!(delay.poll_future(unpark_noop()).unwrap().is_ready());
+}
+
+#[test]
+fn interval() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut interval = executor::spawn(timer.interval(ms(10)));
+    assert!(interval.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(10));
+    assert_eq!(interval.poll_stream(unpark_noop()).unwrap(),
+               futures::Async::Ready(Some(())));
+    assert!(interval.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(25));
+    assert!(interval.poll_stream(unpark_noop()).unwrap().is_ready());
+    assert!(interval.poll_stream(unpark_noop()).unwrap().is_ready());
+    assert!(interval.poll_stream(unpark_noop()).unwrap().is_not_ready());
+}
+
+#[test]
+fn future_timeout() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = oneshot::channel::<i32>();
+    let mut ok = executor::spawn(timer::with_default(&timer, || rx.timeout(ms(10))));
+    let mut elapsed = executor::spawn(timer::with_default(&timer, || {
+        empty::<(), ()>().timeout(ms(10))
+    }));
+    assert!(ok.poll_future(unpark_noop()).unwrap().is_not_ready());
+    assert!(elapsed.poll_future(unpark_noop()).unwrap().is_not_ready());
+
+    tx.complete(1);
+    clock.advance(ms(10));
+    assert_eq!(ok.poll_future(unpark_noop()).unwrap(), futures::Async::Ready(1));
+    assert_eq!(elapsed.poll_future(unpark_noop()), Err(TimeoutError::Elapsed));
+}
+
+#[test]
+fn future_timeout_inner_error() {
+    let (tx, rx) = oneshot::channel::<i32>();
+    drop(tx);
+    match rx.timeout(Duration::from_secs(60)).wait() {
+        Err(TimeoutError::Inner(oneshot::Canceled)) => {}
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn stream_timeout() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.timeout(ms(10))));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    clock.advance(ms(5));
+    tx.send(1).unwrap();
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), futures::Async::Ready(Some(1)));
+
+    // The deadline is reset after each item
+    clock.advance(ms(9));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(1));
+    assert_eq!(s.poll_stream(unpark_noop()), Err(TimeoutError::Elapsed));
+
+    // ... and after an elapsed error
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    tx.send(2).unwrap();
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), futures::Async::Ready(Some(2)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), futures::Async::Ready(None));
+}
+
+#[test]
+fn stream_chunks_timeout() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.chunks_timeout(3, ms(10))));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    // Full chunks are yielded straight away
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(vec![0, 1, 2])));
+
+    // The deadline starts at the first item of a chunk
+    tx.send(3).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(6));
+    tx.send(4).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(4));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(vec![3, 4])));
+
+    // Nothing is yielded while the buffer is empty
+    clock.advance(ms(20));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    tx.send(5).unwrap();
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(vec![5])));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn stream_chunks_timeout_unparks() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.chunks_timeout(3, ms(10))));
+    let unpark = unpark_counter();
+    tx.send(1).unwrap();
+    assert!(s.poll_stream(unpark.clone()).unwrap().is_not_ready());
+    assert_eq!(unpark.count(), 0);
+
+    clock.advance(ms(10));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(s.poll_stream(unpark.clone()).unwrap(), Async::Ready(Some(vec![1])));
+}
+
+#[test]
+fn stream_throttle() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.throttle(ms(10))));
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(1)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    clock.advance(ms(9));
+    tx.send(3).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    clock.advance(ms(1));
+    tx.send(4).unwrap();
+    tx.send(5).unwrap();
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(4)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn stream_debounce() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.debounce(ms(10))));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    // Each new item restarts the wait
+    tx.send(1).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(8));
+    tx.send(2).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(8));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(2));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(2)));
+    clock.advance(ms(20));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    // The waiting item is flushed when the stream ends
+    tx.send(3).unwrap();
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(3)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn stream_sample() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.sample(ms(10))));
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    clock.advance(ms(10));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(2)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    // Ticks without new items are skipped, and once ticks have been missed
+    // the next period starts from the current time
+    clock.advance(ms(25));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    tx.send(3).unwrap();
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(3)));
+
+    tx.send(4).unwrap();
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(4)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn stream_rate_limit() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || rx.rate_limit(2, ms(10))));
+    for i in 0..6 {
+        tx.send(i).unwrap();
+    }
+
+    // A full bucket lets `n` items through at once, then one per interval
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(0)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(1)));
+    let unpark = unpark_counter();
+    assert!(s.poll_stream(unpark.clone()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(2)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+
+    // Idle time refills the bucket, but only up to the burst size
+    clock.advance(ms(100));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(3)));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(4)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    drop(tx);
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(5)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn stream_rate_limit_burst() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || {
+        rx.rate_limit(4, ms(20)).burst(1)
+    }));
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(1)));
+    assert!(s.poll_stream(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(2)));
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn stream_rate_limit_beyond_u32() {
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    // One item per second, with a burst that doesn't fit in a `u32`
+    let n = 1 << 32;
+    let (tx, rx) = mpsc::unbounded::<i32>();
+    let mut s = executor::spawn(timer::with_default(&timer, || {
+        rx.rate_limit(n, Duration::from_secs(n as u64))
+    }));
+    for i in 0..3 {
+        tx.send(i).unwrap();
+    }
+    for i in 0..3 {
+        assert_eq!(s.poll_stream(unpark_noop()).unwrap(), Async::Ready(Some(i)));
+    }
+}
+
+#[test]
+fn sink_rate_limit_backpressure() {
+    use futures::Sink;
+
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let mut sink = timer::with_default(&timer, || Vec::new().rate_limit(1, ms(10)).burst(2));
+    assert_eq!(start_send(&mut sink, 1, unpark_noop()), Ok(AsyncSink::Ready));
+    assert_eq!(start_send(&mut sink, 2, unpark_noop()), Ok(AsyncSink::Ready));
+    let unpark = unpark_counter();
+    assert_eq!(start_send(&mut sink, 3, unpark.clone()), Ok(AsyncSink::NotReady(3)));
+
+    clock.advance(ms(10));
+    timer.turn();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(start_send(&mut sink, 3, unpark_noop()), Ok(AsyncSink::Ready));
+    assert_eq!(start_send(&mut sink, 4, unpark_noop()), Ok(AsyncSink::NotReady(4)));
+    assert_eq!(*sink.get_ref(), [1, 2, 3]);
+}
+
+#[test]
+fn sink_rate_limit_forward() {
+    use futures::Sink;
+
+    let clock = MockClock::new();
+    let timer = Timer::with_clock(clock.clone());
+
+    let items = stream::iter((0..5).map(Ok::<i32, ()>));
+    let sink = timer::with_default(&timer, || Vec::new().rate_limit(2, ms(10)));
+    let mut forward = executor::spawn(items.forward(sink));
+    assert!(forward.poll_future(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(5));
+    assert!(forward.poll_future(unpark_noop()).unwrap().is_not_ready());
+    clock.advance(ms(10));
+    match forward.poll_future(unpark_noop()).unwrap() {
+        Async::Ready((_, sink)) => assert_eq!(sink.into_inner(), [0, 1, 2, 3, 4]),
+        Async::NotReady => panic!("not ready"),
+    }
+}
+
+#[test]
+fn system_timer() {
+    let timer = Timer::new();
+    let start = Instant::now();
+    timer.delay(ms(50)).wait().unwrap();
+    assert!(start.elapsed() >= ms(50));
+
+    let delays = (0..100).map(|i| timer.delay(ms(i % 20)));
+    futures::future::join_all(delays).wait().unwrap();
+}
+
+#[test]
+fn default_timer_timeout() {
+    let start = Instant::now();
+    let res = empty::<(), ()>().timeout(ms(20)).wait();
+    assert_eq!(res, Err(TimeoutError::Elapsed));
+    assert!(start.elapsed() >= ms(20));
+
+    let items = Timer::default().interval(ms(5)).take(3).collect().wait().unwrap();
+    assert_eq!(items, vec![(), (), ()]);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "use_std")]
+
+extern crate futures;
+
+use std::thread;
+
+use futures::{Future, Stream, Async};
+use futures::executor;
+use futures::sync::watch;
+
+mod support;
+use support::*;
+
+fn is_send<T: Send>() {}
+
+#[test]
+fn bounds() {
+    is_send::<watch::Sender<i32>>();
+    is_send::<watch::Receiver<i32>>();
+}
+
+#[test]
+fn borrow_sees_latest() {
+    let (tx, rx) = watch::channel(1);
+    assert_eq!(*rx.borrow(), 1);
+    tx.send(2).unwrap();
+    assert_eq!(*rx.borrow(), 2);
+}
+
+#[test]
+fn only_yields_changes() {
+    let (tx, rx) = watch::channel(0);
+    let mut rx = executor::spawn(rx);
+    let unpark = unpark_counter();
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::NotReady);
+
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(Some(2)));
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::NotReady);
+
+    tx.send(3).unwrap();
+    assert_eq!(unpark.count(), 2);
+    drop(tx);
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(Some(3)));
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn sender_drop_terminates() {
+    let (tx, rx) = watch::channel(0);
+    let mut rx = executor::spawn(rx);
+    let unpark = unpark_counter();
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::NotReady);
+    drop(tx);
+    assert_eq!(unpark.count(), 1);
+    assert_eq!(rx.poll_stream(unpark.clone()).unwrap(), Async::Ready(None));
+}
+
+#[test]
+fn cloned_receivers() {
+    let (tx, rx1) = watch::channel(0);
+    let rx2 = rx1.clone();
+    tx.send(1).unwrap();
+    let rx3 = rx1.clone();
+    drop(tx);
+
+    assert_eq!(rx1.collect().wait(), Ok(vec![1]));
+    assert_eq!(rx2.collect().wait(), Ok(vec![1]));
+    assert_eq!(rx3.collect().wait(), Ok(vec![1]));
+}
+
+#[test]
+fn send_without_receivers() {
+    let (tx, rx) = watch::channel(0);
+    drop(rx);
+    assert_eq!(tx.send(1).unwrap_err().into_inner(), 1);
+}
+
+#[test]
+fn threaded_updates() {
+    let (tx, rx) = watch::channel(0);
+    let t = thread::spawn(move || {
+        let mut last = 0;
+        for value in rx.wait() {
+            let value = value.unwrap();
+            assert!(value > last);
+            last = value;
+        }
+        last
+    });
+    for i in 1..1001 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    assert_eq!(t.join().unwrap(), 1000);
+}